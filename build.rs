@@ -2,6 +2,19 @@ use std::{env, fs, path::Path};
 
 use glob::glob;
 
+// Directories of `sprites/pokemon` holding the sprites which can be displayed, i.e. the default
+// ones and their shiny, female and back variants
+const EMBEDDED_SPRITE_DIRS: [&str; 8] = [
+    "",
+    "shiny/",
+    "female/",
+    "shiny/female/",
+    "back/",
+    "back/shiny/",
+    "back/female/",
+    "back/shiny/female/",
+];
+
 fn main() {
     let out_dir = env::var("OUT_DIR").unwrap();
 
@@ -22,11 +35,18 @@ fn main() {
         let embed_sprites_path =
             env::var("EMBED_SPRITES_PATH").unwrap_or_else(|_| String::from("./result/sprites"));
 
-        let sprite_entries_source = glob(&format!("{embed_sprites_path}/**/*.png"))
-            .unwrap()
+        // Sprites are keyed by their path relative to `sprites/pokemon`, as in their URL, since the
+        // variants share the file names of the default sprites
+        let sprite_entries_source = EMBEDDED_SPRITE_DIRS
+            .iter()
+            .flat_map(|dir| glob(&format!("{embed_sprites_path}/{dir}*.png")).unwrap())
             .filter_map(|entry| entry.ok())
             .map(|path| {
-                let key = path.file_name().unwrap().to_string_lossy();
+                let key = path
+                    .strip_prefix(&embed_sprites_path)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/");
                 let value = fs::canonicalize(&path).unwrap();
                 let value = value.to_string_lossy();
                 format!(r#"("{key}", bytes::Bytes::from_static(include_bytes!("{value}")))"#)
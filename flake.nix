@@ -58,7 +58,7 @@
           buildPhase = ''
             mkdir -p $out/sprites
             cp ${pokeapi}/db.sqlite3 $out/
-            cp ${pokeapi}/sprites/pokemon/*.png $out/sprites
+            cp -r ${pokeapi}/sprites/pokemon/{*.png,shiny,female,back} $out/sprites
           '';
         };
         pokeapi-optimized = pkgs.stdenv.mkDerivation {
@@ -67,11 +67,11 @@
           buildInputs = [pkgs.sqlite];
           buildPhase = ''
             mkdir -p $out/sprites
-            sqlite3 ${pokeapi}/db.sqlite3 ".dump pokemon_v2_pokemon pokemon_v2_pokemonsprites pokemon_v2_pokemonspecies pokemon_v2_pokemonspeciesname pokemon_v2_language" | sqlite3 $out/db.sqlite3
+            sqlite3 ${pokeapi}/db.sqlite3 ".dump pokemon_v2_pokemon pokemon_v2_pokemonsprites pokemon_v2_pokemonspecies pokemon_v2_pokemonspeciesname pokemon_v2_language pokemon_v2_pokemontype pokemon_v2_type pokemon_v2_pokemonevolution pokemon_v2_evolutiontrigger pokemon_v2_evolutionchain pokemon_v2_item pokemon_v2_pokemonstat pokemon_v2_stat pokemon_v2_pokemonability pokemon_v2_ability pokemon_v2_pokemonmove pokemon_v2_move pokemon_v2_movename pokemon_v2_movedamageclass pokemon_v2_typeefficacy" | sqlite3 $out/db.sqlite3
             sqlite3 $out/db.sqlite3 < ${./pokeapi-optimize-db-table.sql} > $out/optimize.sql
             sqlite3 $out/db.sqlite3 < $out/optimize.sql
             rm $out/optimize.sql
-            cp -r ${pokeapi}/sprites/pokemon/*.png $out/sprites
+            cp -r ${pokeapi}/sprites/pokemon/{*.png,shiny,female,back} $out/sprites
           '';
         };
         sea-orm-cli = craneLib.buildPackage {
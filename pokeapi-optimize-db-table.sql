@@ -1,3 +1,15 @@
 SELECT 'ALTER TABLE pokemon_v2_pokemonspecies DROP COLUMN "' || name || '";'
 FROM pragma_table_info('pokemon_v2_pokemonspecies')
-WHERE name != 'id';
+WHERE name NOT IN (
+    'id',
+    'capture_rate',
+    'evolution_chain_id',
+    'evolves_from_species_id',
+    'gender_rate',
+    'generation_id',
+    'growth_rate_id',
+    'has_gender_differences'
+);
+
+-- A Pokemon's moves are only needed once, regardless of the game or how they're learned
+SELECT 'DELETE FROM pokemon_v2_pokemonmove WHERE id NOT IN (SELECT MIN(id) FROM pokemon_v2_pokemonmove GROUP BY pokemon_id, move_id);';
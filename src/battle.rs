@@ -0,0 +1,362 @@
+use std::cmp::Ordering;
+
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+use crate::{
+    BattleOpt, CropMode, Effect, GraphicsProtocol, Move, Opt, Pokemon, Stats, TypeEfficacy,
+    get_battle_details, get_pokemon_by_species, get_species, get_sprite, get_type_efficacy,
+    new_graphics, print_sprite, sprite_variant_url, terminal, title_case,
+};
+
+/// Rows reserved below the sprites for the combatants' moves and the start of the battle log
+const RESERVED_ROWS: u32 = 6;
+
+const MOVES_PER_POKEMON: usize = 2;
+
+// Battles between Pokemon that can't hurt each other end in a draw
+const MAX_TURNS: u32 = 100;
+
+// Chance of landing a critical hit since generation VII
+const CRITICAL_HIT_ODDS: u32 = 24;
+
+// Both Pokemon have perfect IVs, no EVs and a neutral nature
+const IV: i64 = 31;
+
+struct Combatant {
+    name: String,
+    types: Vec<String>,
+    stats: Stats,
+    moves: Vec<Move>,
+    hp: i64,
+}
+
+impl Combatant {
+    async fn new(
+        opt: &Opt,
+        pokemon: &Pokemon,
+        level: i64,
+        rng: &mut StdRng,
+    ) -> anyhow::Result<Self> {
+        let battle_details = get_battle_details(opt, pokemon).await?;
+
+        let mut moves = battle_details
+            .moves
+            .choose_multiple(rng, MOVES_PER_POKEMON)
+            .cloned()
+            .collect::<Vec<_>>();
+        if moves.is_empty() {
+            moves.push(struggle());
+        }
+
+        let base_stats = battle_details.base_stats;
+        let stats = Stats {
+            hp: hp_at_level(base_stats.hp, level),
+            attack: stat_at_level(base_stats.attack, level),
+            defense: stat_at_level(base_stats.defense, level),
+            special_attack: stat_at_level(base_stats.special_attack, level),
+            special_defense: stat_at_level(base_stats.special_defense, level),
+            speed: stat_at_level(base_stats.speed, level),
+        };
+
+        Ok(Self {
+            name: pokemon.name.to_uppercase(),
+            types: pokemon.types.clone(),
+            stats,
+            moves,
+            hp: stats.hp,
+        })
+    }
+
+    fn fainted(&self) -> bool {
+        self.hp == 0
+    }
+}
+
+pub async fn run(opt: &Opt, battle_opt: &BattleOpt) -> anyhow::Result<()> {
+    let mut rng = match battle_opt.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+
+    // Picking from every species instead of using the Pokemon query keeps seeded battles the same
+    let species = get_species(opt).await?;
+    if species.len() < 2 {
+        return Err(anyhow::anyhow!("Not enough Pokemon to battle!"));
+    }
+    let player_index = rng.random_range(0..species.len());
+    let opponent_index = (player_index + rng.random_range(1..species.len())) % species.len();
+    let player_pokemon = get_pokemon_by_species(opt, species[player_index].id).await?;
+    let opponent_pokemon = get_pokemon_by_species(opt, species[opponent_index].id).await?;
+
+    let mut player = Combatant::new(opt, &player_pokemon, battle_opt.level, &mut rng).await?;
+    let mut opponent = Combatant::new(opt, &opponent_pokemon, battle_opt.level, &mut rng).await?;
+    let type_efficacy = get_type_efficacy(opt).await?;
+
+    let graphics = new_graphics(opt);
+    if graphics.protocol() != GraphicsProtocol::Text {
+        let front = get_sprite(opt, &opponent_pokemon.sprite_url).await?;
+        // Fall back to mirroring the front sprite when the back sprite is missing
+        let back = match sprite_variant_url(&player_pokemon.sprite_url, "back/") {
+            Some(back_sprite_url) => get_sprite(opt, &back_sprite_url).await.ok(),
+            None => None,
+        };
+        let back = match back {
+            Some(back) => back,
+            None => crate::image_util::apply_effect(
+                &get_sprite(opt, &player_pokemon.sprite_url).await?,
+                Effect::FlipH,
+            ),
+        };
+
+        let scene = crate::image_util::face_off(
+            &crate::image_util::crop_transparent_pixels(&back, 0, 1, CropMode::Bbox),
+            &crate::image_util::crop_transparent_pixels(&front, 0, 1, CropMode::Bbox),
+        );
+        let sprite_bounds = terminal::size(opt.columns, opt.rows).map(|(columns, rows)| {
+            (
+                u32::from(columns),
+                u32::from(rows).saturating_sub(RESERVED_ROWS),
+            )
+        });
+        print_sprite(opt, &graphics, scene, sprite_bounds)?;
+    }
+
+    for combatant in [&player, &opponent] {
+        let types = combatant
+            .types
+            .iter()
+            .map(|pokemon_type| title_case(pokemon_type))
+            .collect::<Vec<_>>();
+        let moves = combatant
+            .moves
+            .iter()
+            .map(|pokemon_move| pokemon_move.name.as_str())
+            .collect::<Vec<_>>();
+        println!(
+            "{} Lv. {} ({}) knows {}",
+            combatant.name,
+            battle_opt.level,
+            types.join("/"),
+            moves.join(" and ")
+        );
+    }
+
+    for turn in 1..=MAX_TURNS {
+        println!();
+        println!("Turn {turn}");
+
+        let player_move = player
+            .moves
+            .choose(&mut rng)
+            .cloned()
+            .unwrap_or_else(struggle);
+        let opponent_move = opponent
+            .moves
+            .choose(&mut rng)
+            .cloned()
+            .unwrap_or_else(struggle);
+        let player_first = match player_move
+            .priority
+            .cmp(&opponent_move.priority)
+            .then(player.stats.speed.cmp(&opponent.stats.speed))
+        {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => rng.random_bool(0.5),
+        };
+
+        let (first, first_move, second, second_move) = if player_first {
+            (&mut player, player_move, &mut opponent, opponent_move)
+        } else {
+            (&mut opponent, opponent_move, &mut player, player_move)
+        };
+        attack(
+            first,
+            second,
+            &first_move,
+            battle_opt.level,
+            &type_efficacy,
+            &mut rng,
+        );
+        if !second.fainted() {
+            attack(
+                second,
+                first,
+                &second_move,
+                battle_opt.level,
+                &type_efficacy,
+                &mut rng,
+            );
+        }
+
+        let (winner, loser) = match (player.fainted(), opponent.fainted()) {
+            (false, true) => (&player, &opponent),
+            (true, false) => (&opponent, &player),
+            _ => continue,
+        };
+        println!("{} fainted!", loser.name);
+        println!();
+        println!(
+            "{} won after {turn} turn{} with {}/{} HP left!",
+            winner.name,
+            if turn == 1 { "" } else { "s" },
+            winner.hp,
+            winner.stats.hp
+        );
+        return Ok(());
+    }
+
+    println!();
+    println!("Neither Pokémon fainted after {MAX_TURNS} turns, it's a draw!");
+
+    Ok(())
+}
+
+/// Uses a move on the defender with the damage formula of the games since generation V
+fn attack(
+    attacker: &Combatant,
+    defender: &mut Combatant,
+    pokemon_move: &Move,
+    level: i64,
+    type_efficacy: &TypeEfficacy,
+    rng: &mut StdRng,
+) {
+    println!("{} used {}!", attacker.name, pokemon_move.name);
+
+    if let Some(accuracy) = pokemon_move.accuracy
+        && rng.random_range(0..100) >= accuracy
+    {
+        println!("{}'s attack missed!", attacker.name);
+        return;
+    }
+
+    let effectiveness = effectiveness(&pokemon_move.move_type, &defender.types, type_efficacy);
+    if effectiveness == 0.0 {
+        println!("It doesn't affect {}...", defender.name);
+        return;
+    }
+
+    let (attack, defense) = if pokemon_move.damage_class == "special" {
+        (
+            attacker.stats.special_attack,
+            defender.stats.special_defense,
+        )
+    } else {
+        (attacker.stats.attack, defender.stats.defense)
+    };
+    let base_damage = base_damage(level, pokemon_move.power, attack, defense);
+
+    let critical = rng.random_ratio(1, CRITICAL_HIT_ODDS);
+    let same_type_attack_bonus = attacker.types.contains(&pokemon_move.move_type);
+    let mut modifier = rng.random_range(85..=100) as f64 / 100.0 * effectiveness;
+    if critical {
+        modifier *= 1.5;
+    }
+    if same_type_attack_bonus {
+        modifier *= 1.5;
+    }
+    let damage = ((base_damage as f64 * modifier).floor() as i64).max(1);
+
+    if critical {
+        println!("A critical hit!");
+    }
+    if effectiveness > 1.0 {
+        println!("It's super effective!");
+    } else if effectiveness < 1.0 {
+        println!("It's not very effective...");
+    }
+
+    defender.hp = (defender.hp - damage).max(0);
+    println!(
+        "{} has {}/{} HP left.",
+        defender.name, defender.hp, defender.stats.hp
+    );
+}
+
+/// Damage before the random factor, critical hits, STAB and type effectiveness are applied
+fn base_damage(level: i64, power: i64, attack: i64, defense: i64) -> i64 {
+    (2 * level / 5 + 2) * power * attack / defense.max(1) / 50 + 2
+}
+
+/// Damage multiplier of a move's type against each of the defender's types
+fn effectiveness(move_type: &str, defender_types: &[String], type_efficacy: &TypeEfficacy) -> f64 {
+    defender_types
+        .iter()
+        .map(|defender_type| {
+            let damage_factor = type_efficacy
+                .get(&(String::from(move_type), defender_type.clone()))
+                .copied()
+                .unwrap_or(100);
+            damage_factor as f64 / 100.0
+        })
+        .product()
+}
+
+fn hp_at_level(base_hp: i64, level: i64) -> i64 {
+    (2 * base_hp + IV) * level / 100 + level + 10
+}
+
+fn stat_at_level(base_stat: i64, level: i64) -> i64 {
+    (2 * base_stat + IV) * level / 100 + 5
+}
+
+/// Used by Pokemon that don't know any damaging moves, hitting without a type
+fn struggle() -> Move {
+    Move {
+        name: String::from("Struggle"),
+        power: 50,
+        accuracy: None,
+        priority: 0,
+        move_type: String::new(),
+        damage_class: String::from("physical"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_match_the_games_with_perfect_ivs() {
+        assert_eq!(hp_at_level(100, 50), 175);
+        assert_eq!(hp_at_level(255, 100), 651);
+        assert_eq!(stat_at_level(100, 50), 120);
+        assert_eq!(stat_at_level(5, 100), 46);
+    }
+
+    #[test]
+    fn base_damage_follows_the_formula() {
+        assert_eq!(base_damage(50, 90, 120, 120), 41);
+        assert_eq!(base_damage(100, 40, 200, 100), 2 + 42 * 40 * 200 / 100 / 50);
+    }
+
+    #[test]
+    fn base_damage_survives_zero_defense() {
+        assert_eq!(base_damage(5, 40, 10, 0), base_damage(5, 40, 10, 1));
+    }
+
+    #[test]
+    fn effectiveness_multiplies_the_defender_types() {
+        let type_efficacy = TypeEfficacy::from([
+            ((String::from("fire"), String::from("grass")), 200),
+            ((String::from("fire"), String::from("bug")), 200),
+            ((String::from("fire"), String::from("water")), 50),
+            ((String::from("normal"), String::from("ghost")), 0),
+        ]);
+        let types = |types: &[&str]| types.iter().copied().map(String::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            effectiveness("fire", &types(&["grass", "bug"]), &type_efficacy),
+            4.0
+        );
+        assert_eq!(
+            effectiveness("fire", &types(&["water", "grass"]), &type_efficacy),
+            1.0
+        );
+        assert_eq!(
+            effectiveness("normal", &types(&["ghost"]), &type_efficacy),
+            0.0
+        );
+        assert_eq!(effectiveness("", &types(&["grass"]), &type_efficacy), 1.0);
+    }
+}
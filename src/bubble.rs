@@ -0,0 +1,196 @@
+use std::str::Chars;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::color::RESET_FOREGROUND;
+
+const ESCAPE: char = '\x1b';
+
+const PADDING: usize = 8;
+// Used instead of `PADDING` when the message would otherwise be wrapped narrower than this
+const MIN_TEXT_WIDTH: usize = 24;
+const NARROW_PADDING: usize = 2;
+const _: () = assert!(PADDING.is_multiple_of(2) && NARROW_PADDING.is_multiple_of(2));
+
+/// Renders the message inside a speech bubble, wrapping it to fit within `max_width` columns.
+/// The border is drawn after the `border_style` escape sequence when given.
+pub fn render(message: &str, max_width: Option<usize>, border_style: Option<&str>) -> Vec<String> {
+    let message_width = message.split('\n').map(width).max();
+    let message_width = message_width.unwrap_or(0);
+
+    let (padding, text_width) = match max_width {
+        Some(max_width) => {
+            // Leave room for the borders on either side
+            let available = max_width.saturating_sub(2);
+            let padding = if message_width + PADDING <= available
+                || available.saturating_sub(PADDING) >= MIN_TEXT_WIDTH
+            {
+                PADDING
+            } else {
+                NARROW_PADDING
+            };
+            (padding, available.saturating_sub(padding).max(1))
+        }
+        None => (PADDING, message_width.max(1)),
+    };
+
+    let lines = message
+        .split('\n')
+        .flat_map(|line| wrap(line, text_width))
+        .collect::<Vec<_>>();
+    let inner_width = lines.iter().map(|line| width(line)).max().unwrap_or(0) + padding;
+
+    let style = |border: &str| match border_style {
+        Some(border_style) => format!("{border_style}{border}{RESET_FOREGROUND}"),
+        None => String::from(border),
+    };
+    let edge = style(&format!("◓{}◓", "═".repeat(inner_width)));
+    let side = style("‖");
+
+    let mut bubble = Vec::with_capacity(lines.len() + 4);
+    bubble.push(edge.clone());
+    bubble.push(format!("{side}{}{side}", " ".repeat(inner_width)));
+    for line in lines {
+        // Center each line, favoring the left when it can't be exactly centered
+        let left = (inner_width - width(&line)) / 2;
+        let right = inner_width - width(&line) - left;
+        bubble.push(format!(
+            "{side}{}{line}{}{side}",
+            " ".repeat(left),
+            " ".repeat(right)
+        ));
+    }
+    bubble.push(format!("{side}{}{side}", " ".repeat(inner_width)));
+    bubble.push(edge);
+    bubble
+}
+
+/// Width of the text in terminal columns, skipping the escape sequences coloring it
+fn width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == ESCAPE {
+            skip_escape_sequence(&mut chars, |_| {});
+        } else {
+            width += c.width().unwrap_or(0);
+        }
+    }
+    width
+}
+
+/// Consumes the rest of an escape sequence like `\x1b[38;5;208m` after its `ESC`
+fn skip_escape_sequence(chars: &mut Chars, mut f: impl FnMut(char)) {
+    // The introducer, e.g. `[`
+    if let Some(c) = chars.next() {
+        f(c);
+    }
+    for c in chars.by_ref() {
+        f(c);
+        if ('\x40'..='\x7e').contains(&c) {
+            break;
+        }
+    }
+}
+
+/// Wraps a line on whitespace, breaking words which are wider than `width` on their own
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    if self::width(line) <= width {
+        return vec![String::from(line)];
+    }
+
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in line.split_whitespace() {
+        let separator = usize::from(!current.is_empty());
+        if self::width(&current) + separator + self::width(word) <= width {
+            if separator == 1 {
+                current.push(' ');
+            }
+            current.push_str(word);
+            continue;
+        }
+
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        let mut chars = word.chars();
+        while let Some(c) = chars.next() {
+            // Escape sequences take no room, so keep them whole on the current line
+            if c == ESCAPE {
+                current.push(c);
+                skip_escape_sequence(&mut chars, |c| current.push(c));
+                continue;
+            }
+            if self::width(&current) + c.width().unwrap_or(0) > width && !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            current.push(c);
+        }
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YELLOW: &str = "\x1b[38;5;220m";
+
+    #[test]
+    fn width_skips_escape_sequences() {
+        assert_eq!(width("PIKACHU"), 7);
+        assert_eq!(width(&format!("{YELLOW}PIKACHU{RESET_FOREGROUND}")), 7);
+        assert_eq!(width(""), 0);
+    }
+
+    #[test]
+    fn width_counts_wide_characters_twice() {
+        assert_eq!(width("ピカチュウ"), 10);
+        assert_eq!(width("Pokémon"), 7);
+    }
+
+    #[test]
+    fn wrap_breaks_on_whitespace() {
+        assert_eq!(
+            wrap("Wild PIKACHU appeared!", 30),
+            ["Wild PIKACHU appeared!"]
+        );
+        assert_eq!(
+            wrap("Wild PIKACHU appeared!", 12),
+            ["Wild PIKACHU", "appeared!"]
+        );
+    }
+
+    #[test]
+    fn wrap_ignores_escape_sequences_when_measuring() {
+        let line = format!("Wild {YELLOW}PIKACHU{RESET_FOREGROUND} appeared!");
+        assert_eq!(
+            wrap(&line, 12),
+            [
+                format!("Wild {YELLOW}PIKACHU{RESET_FOREGROUND}"),
+                String::from("appeared!")
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_breaks_long_words_keeping_escape_sequences_whole() {
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(
+            wrap(&format!("{YELLOW}abcdef{RESET_FOREGROUND}"), 3),
+            [format!("{YELLOW}abc"), format!("def{RESET_FOREGROUND}")]
+        );
+    }
+
+    #[test]
+    fn render_pads_every_line_to_the_same_width() {
+        let message = format!("Wild {YELLOW}PIKACHU{RESET_FOREGROUND} appeared!\nIt's shiny!");
+        let bubble = render(&message, Some(20), None);
+        assert!(bubble.iter().all(|line| width(line) == width(&bubble[0])));
+        assert!(width(&bubble[0]) <= 20);
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+
+use crate::{
+    BuddyOpt, Opt, bubble, find_species_id, get_evolutions, get_pokemon, get_pokemon_by_species,
+    get_pokemon_details, get_pokemon_sprite, local_db::LocalDb, new_graphics, print_sprite,
+    terminal,
+};
+
+const STARTING_LEVEL: i64 = 5;
+const MAX_LEVEL: i64 = 100;
+
+const EVOLUTION_STEPS: u32 = 5;
+const EVOLUTION_DELAY: Duration = Duration::from_millis(600);
+
+pub async fn run(opt: &Opt, buddy_opt: &BuddyOpt) -> anyhow::Result<()> {
+    let local_db = LocalDb::new(&opt.data_dir).await?;
+    let current_buddy = local_db.get_buddy().await?;
+
+    let species_id = match (&buddy_opt.pokemon, &current_buddy) {
+        (Some(name), _) => find_species_id(opt, name).await?,
+        (None, Some(buddy)) => buddy.species_id,
+        (None, None) => get_pokemon(opt).await?.species_id,
+    };
+    let pokemon = get_pokemon_by_species(opt, species_id).await?;
+    let details = get_pokemon_details(opt, &pokemon).await?;
+    let name = pokemon.name.to_uppercase();
+
+    let mut messages = Vec::new();
+    let (buddy, level) = match current_buddy {
+        Some(buddy) if buddy_opt.pokemon.is_none() => {
+            let level = level_for_experience(details.growth_rate, buddy.experience);
+            (buddy, level)
+        }
+        _ => {
+            let experience = experience_for_level(details.growth_rate, STARTING_LEVEL);
+            messages.push(format!("You chose {name} as your buddy!"));
+            (
+                local_db.set_buddy(species_id, experience).await?,
+                STARTING_LEVEL,
+            )
+        }
+    };
+
+    // Experience for defeating a wild Pokemon of the same level, at least 1 point even when the
+    // base experience is unknown
+    let gained_experience = (details.base_experience.unwrap_or_default() * level / 7).max(1);
+    let experience = (buddy.experience + gained_experience)
+        .min(experience_for_level(details.growth_rate, MAX_LEVEL));
+    let new_level = level_for_experience(details.growth_rate, experience);
+    if level < MAX_LEVEL {
+        messages.push(format!("{name} gained {gained_experience} Exp. Points!"));
+    }
+    if new_level > level {
+        messages.push(format!("{name} grew to Lv. {new_level}!"));
+    } else {
+        messages.push(format!("{name} is Lv. {new_level}."));
+    }
+
+    let terminal_size = terminal::size(opt.columns, opt.rows)
+        .map(|(columns, rows)| (u32::from(columns), u32::from(rows)));
+    let bubble = bubble::render(
+        &messages.join("\n"),
+        terminal_size.map(|(columns, _)| columns as usize),
+        None,
+    );
+    let sprite_bounds = terminal_size
+        .map(|(columns, rows)| (columns, rows.saturating_sub(bubble.len() as u32 + 1)));
+
+    let graphics = new_graphics(opt);
+    let sprite_image = get_pokemon_sprite(opt, &pokemon).await?;
+    print_sprite(opt, &graphics, sprite_image, sprite_bounds)?;
+    for line in bubble {
+        println!("{line}");
+    }
+
+    let evolution = get_evolutions(opt, species_id)
+        .await?
+        .into_iter()
+        .find(|evolution| {
+            evolution.trigger == "level-up"
+                && evolution
+                    .min_level
+                    .is_some_and(|min_level| min_level <= new_level)
+        });
+    let species_id = match evolution {
+        Some(evolution) => {
+            let evolved_pokemon = get_pokemon_by_species(opt, evolution.species_id).await?;
+
+            print!("What? {name} is evolving!");
+            io::stdout().flush()?;
+            for _ in 0..EVOLUTION_STEPS {
+                tokio::time::sleep(EVOLUTION_DELAY).await;
+                print!(".");
+                io::stdout().flush()?;
+            }
+            println!();
+
+            let sprite_image = get_pokemon_sprite(opt, &evolved_pokemon).await?;
+            print_sprite(opt, &graphics, sprite_image, sprite_bounds)?;
+            println!(
+                "Congratulations! Your {name} evolved into {}!",
+                evolved_pokemon.name.to_uppercase()
+            );
+            evolution.species_id
+        }
+        None => species_id,
+    };
+
+    local_db.update_buddy(buddy, species_id, experience).await?;
+
+    Ok(())
+}
+
+fn level_for_experience(growth_rate: i64, experience: i64) -> i64 {
+    (2..=MAX_LEVEL)
+        .take_while(|&level| experience_for_level(growth_rate, level) <= experience)
+        .last()
+        .unwrap_or(1)
+}
+
+/// Total experience needed to reach a level, following the growth rates from the games
+fn experience_for_level(growth_rate: i64, level: i64) -> i64 {
+    if level <= 1 {
+        return 0;
+    }
+
+    let n = level;
+    let cube = n.pow(3);
+    match growth_rate {
+        // Slow
+        1 => 5 * cube / 4,
+        // Fast
+        3 => 4 * cube / 5,
+        // Medium slow
+        4 => 6 * cube / 5 - 15 * n * n + 100 * n - 140,
+        // Slow then very fast, a.k.a. erratic
+        5 => match n {
+            ..50 => cube * (100 - n) / 50,
+            50..68 => cube * (150 - n) / 100,
+            68..98 => cube * ((1911 - 10 * n) / 3) / 500,
+            _ => cube * (160 - n) / 100,
+        },
+        // Fast then very slow, a.k.a. fluctuating
+        6 => match n {
+            ..15 => cube * ((n + 1) / 3 + 24) / 50,
+            15..36 => cube * (n + 14) / 50,
+            _ => cube * (n / 2 + 32) / 50,
+        },
+        // Medium
+        _ => cube,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn experience_for_level_matches_the_games_at_level_100() {
+        assert_eq!(experience_for_level(1, 100), 1_250_000);
+        assert_eq!(experience_for_level(2, 100), 1_000_000);
+        assert_eq!(experience_for_level(3, 100), 800_000);
+        assert_eq!(experience_for_level(4, 100), 1_059_860);
+        assert_eq!(experience_for_level(5, 100), 600_000);
+        assert_eq!(experience_for_level(6, 100), 1_640_000);
+    }
+
+    #[test]
+    fn experience_for_level_matches_the_games_at_lower_levels() {
+        assert_eq!(experience_for_level(4, 5), 135);
+        assert_eq!(experience_for_level(5, 50), 125_000);
+        assert_eq!(experience_for_level(6, 50), 142_500);
+        assert_eq!(experience_for_level(2, 1), 0);
+    }
+
+    #[test]
+    fn level_for_experience_inverts_experience_for_level() {
+        for growth_rate in 1..=6 {
+            for level in [STARTING_LEVEL, 16, 36, 50, 68, 98, MAX_LEVEL] {
+                let experience = experience_for_level(growth_rate, level);
+                assert_eq!(level_for_experience(growth_rate, experience), level);
+                assert_eq!(level_for_experience(growth_rate, experience - 1), level - 1);
+            }
+        }
+    }
+
+    #[test]
+    fn level_for_experience_is_capped() {
+        assert_eq!(level_for_experience(2, 0), 1);
+        assert_eq!(level_for_experience(2, 124), 4);
+        assert_eq!(level_for_experience(2, i64::MAX), MAX_LEVEL);
+    }
+}
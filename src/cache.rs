@@ -0,0 +1,396 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    CacheMode, Opt, PrefetchOpt, SpriteRetrievalMethod, get_species, http::Http, roman_numeral,
+    sprite_variant_url, xdg_dir,
+};
+
+// Variants of the sprites shown besides the default one, i.e. shiny or female Pokemon and the
+// back of the player's Pokemon in battles
+const PREFETCHED_SPRITE_VARIANTS: [&str; 4] = ["shiny/", "female/", "shiny/female/", "back/"];
+
+/// Directory of files in the XDG cache directory, keyed by the hash of what they were made from
+#[derive(Debug, Clone)]
+pub struct Cache {
+    /// `None` when there's no cache directory on this platform
+    dir: Option<PathBuf>,
+    mode: CacheMode,
+    /// Size in bytes above which the least recently used entries are evicted
+    max_size: Option<u64>,
+    /// Age above which entries are deleted, as no lookup would use them anymore
+    max_age: Option<Duration>,
+    /// Whether the entries older than `max_age` were deleted, which the first write does
+    pruned: Arc<AtomicBool>,
+    /// Total size of the entries in bytes, scanned by the first write and then kept up to date by
+    /// the following ones, so that the directory is only scanned again to evict entries
+    size: Arc<Mutex<Option<u64>>>,
+}
+
+impl Cache {
+    /// Opens the cache in `pokemonsay/{name}` in the XDG cache directory, i.e.
+    /// `$XDG_CACHE_HOME/pokemonsay/{name}` or `~/.cache/pokemonsay/{name}` on every platform
+    pub fn new(name: &str, mode: CacheMode) -> Self {
+        Self {
+            dir: xdg_dir("XDG_CACHE_HOME", ".cache")
+                .map(|cache_dir| cache_dir.join(env!("CARGO_PKG_NAME")).join(name)),
+            mode,
+            max_size: None,
+            max_age: None,
+            pruned: Arc::new(AtomicBool::new(false)),
+            size: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Limits the size of the cache, evicting the least recently used entries when it's exceeded.
+    /// Entries are then kept regardless of their age.
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Deletes the entries older than `max_age`, which should be the longest `ttl` they're read
+    /// with
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Hashes the contents into a key, e.g. a query along with its variables
+    pub fn key(contents: &[u8]) -> String {
+        Sha256::digest(contents)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// Reads the entry of the key unless it's older than the `ttl`
+    pub fn get(&self, key: &str, ttl: Option<Duration>) -> Option<Vec<u8>> {
+        if !matches!(self.mode, CacheMode::On | CacheMode::ReadOnly) {
+            return None;
+        }
+        let path = self.path(key)?;
+        if let Some(ttl) = ttl {
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()?;
+            if age(modified) > ttl {
+                if self.max_age.is_some_and(|max_age| age(modified) > max_age)
+                    && matches!(self.mode, CacheMode::On)
+                {
+                    let _ = fs::remove_file(&path);
+                }
+                return None;
+            }
+        }
+        let contents = fs::read(&path).ok()?;
+        if self.max_size.is_some() {
+            // The modification time tracks the last use, which decides what to evict first
+            let _ = fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(SystemTime::now()));
+        }
+        Some(contents)
+    }
+
+    /// Writes the entry of the key. The cache only spares requests, so failing to write is ignored.
+    pub fn put(&self, key: &str, contents: &[u8]) {
+        if !matches!(self.mode, CacheMode::On | CacheMode::Refresh) {
+            return;
+        }
+        let Some(path) = self.path(key) else {
+            return;
+        };
+        let replaced_len = fs::metadata(&path).map_or(0, |metadata| metadata.len());
+        if write_atomically(&path, contents).is_err() {
+            return;
+        }
+        if let Some(max_age) = self.max_age
+            && !self.pruned.swap(true, Ordering::Relaxed)
+        {
+            let _ = self.prune(max_age);
+        }
+        if let Some(max_size) = self.max_size {
+            let mut size = self.size.lock().unwrap_or_else(PoisonError::into_inner);
+            let new_size = match *size {
+                Some(size) => (size + contents.len() as u64).saturating_sub(replaced_len),
+                None => match self.entries() {
+                    Ok(entries) => entries.iter().map(|&(_, len, _)| len).sum(),
+                    Err(_) => return,
+                },
+            };
+            *size = Some(if new_size > max_size {
+                self.evict(max_size)
+            } else {
+                new_size
+            });
+        }
+    }
+
+    /// Removes the entry of the key, e.g. when it refers to an evicted entry
+    pub fn remove(&self, key: &str) {
+        if !matches!(self.mode, CacheMode::On) {
+            return;
+        }
+        let Some(path) = self.path(key) else {
+            return;
+        };
+        let Ok(metadata) = fs::metadata(&path) else {
+            return;
+        };
+        if fs::remove_file(&path).is_ok()
+            && let Some(size) = self
+                .size
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .as_mut()
+        {
+            *size = size.saturating_sub(metadata.len());
+        }
+    }
+
+    /// Removes the least recently used entries until the cache fits within 90% of `max_size`
+    /// bytes, leaving room for the next writes before the directory has to be scanned again.
+    /// Returns the size of the remaining entries.
+    fn evict(&self, max_size: u64) -> u64 {
+        let target_size = max_size / 10 * 9;
+        let mut entries = self.entries().unwrap_or_default();
+        let mut size = entries.iter().map(|&(_, len, _)| len).sum::<u64>();
+        entries.sort();
+        for (_, len, path) in entries {
+            if size <= target_size {
+                break;
+            }
+            if fs::remove_file(path).is_ok() {
+                size -= len;
+            }
+        }
+        size
+    }
+
+    /// Deletes the entries older than `max_age`
+    fn prune(&self, max_age: Duration) -> std::io::Result<()> {
+        for (modified, _, path) in self.entries()? {
+            if age(modified) > max_age {
+                let _ = fs::remove_file(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Modification time, size and path of every entry. Unreadable entries, stray files and the
+    /// temporary files of writes in progress are skipped.
+    fn entries(&self) -> std::io::Result<Vec<(SystemTime, u64, PathBuf)>> {
+        let Some(dir) = &self.dir else {
+            return Ok(Vec::new());
+        };
+        let mut entries = Vec::new();
+        for prefix_dir in fs::read_dir(dir)?.flatten() {
+            if !prefix_dir
+                .file_type()
+                .is_ok_and(|file_type| file_type.is_dir())
+            {
+                continue;
+            }
+            let Ok(prefix_entries) = fs::read_dir(prefix_dir.path()) else {
+                continue;
+            };
+            for entry in prefix_entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|extension| extension == "tmp") {
+                    continue;
+                }
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if let (true, Ok(modified)) = (metadata.is_file(), metadata.modified()) {
+                    entries.push((modified, metadata.len(), path));
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Entries are spread over subdirectories named by the first 2 characters of their key, like
+    /// git objects, to keep directories small
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        let (prefix, rest) = key.split_at_checked(2)?;
+        Some(self.dir.as_ref()?.join(prefix).join(rest))
+    }
+}
+
+/// Time elapsed since the modification time, which is zero if it's in the future
+fn age(modified: SystemTime) -> Duration {
+    SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default()
+}
+
+/// Writes to a temporary file renamed over the entry, so that concurrent runs never read a
+/// partially written entry
+fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temporary_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&temporary_path, contents)?;
+    fs::rename(&temporary_path, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temporary_path);
+    })
+}
+
+/// Downloads the sprites of every Pokemon species of the generations, along with their shiny,
+/// female and back variants, into the cache, so that they're available offline. The sprites of
+/// the alternate forms, e.g. Alolan Pokemon, aren't downloaded.
+pub async fn prefetch(opt: &Opt, prefetch_opt: &PrefetchOpt) -> anyhow::Result<()> {
+    if !matches!(opt.sprites_retrieval_method, SpriteRetrievalMethod::Http) {
+        return Err(anyhow::anyhow!(
+            "Sprites are only cached with `--sprites-retrieval-method http`!"
+        ));
+    }
+    if !matches!(opt.cache, CacheMode::On | CacheMode::Refresh) {
+        return Err(anyhow::anyhow!(
+            "Sprites can't be cached with `--cache off` or `--cache read-only`!"
+        ));
+    }
+
+    let http = Http::new(opt.cache).with_sprite_cache_size(opt.sprite_cache_size);
+    let species = get_species(opt).await?;
+    let mut generations = species
+        .iter()
+        .map(|species| species.generation)
+        .filter(|generation| {
+            prefetch_opt.generations.is_empty() || prefetch_opt.generations.contains(generation)
+        })
+        .collect::<Vec<_>>();
+    generations.sort_unstable();
+    generations.dedup();
+
+    for generation in generations {
+        let (mut cached, mut failed) = (0, 0);
+        for species in species
+            .iter()
+            .filter(|species| species.generation == generation && !species.sprite_url.is_empty())
+        {
+            let variant_urls = PREFETCHED_SPRITE_VARIANTS
+                .iter()
+                .filter_map(|variant| sprite_variant_url(&species.sprite_url, variant));
+            for (i, url) in [species.sprite_url.clone()]
+                .into_iter()
+                .chain(variant_urls)
+                .enumerate()
+            {
+                match http.get_sprite(&url).await {
+                    Ok(_) => cached += 1,
+                    // Most Pokemon have no female variant, and some have no back sprite
+                    Err(err) if i > 0 && is_not_found(&err) => {}
+                    Err(_) => failed += 1,
+                }
+            }
+        }
+        print!(
+            "Generation {}: {cached} sprites cached",
+            roman_numeral(generation)
+        );
+        if failed > 0 {
+            print!(", {failed} failed");
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .and_then(|err| err.status())
+        == Some(reqwest::StatusCode::NOT_FOUND)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn cache(name: &str) -> Cache {
+        let dir = env::temp_dir().join(format!("pokemonsay-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Cache {
+            dir: Some(dir),
+            ..Cache::new(name, CacheMode::On)
+        }
+    }
+
+    fn set_modified(cache: &Cache, key: &str, modified: SystemTime) {
+        fs::File::options()
+            .write(true)
+            .open(cache.path(key).unwrap())
+            .and_then(|file| file.set_modified(modified))
+            .unwrap();
+    }
+
+    #[test]
+    fn evict_removes_the_least_recently_used_entries_and_skips_stray_files() {
+        let cache = cache("evict").with_max_size(130);
+        let dir = cache.dir.clone().unwrap();
+        fs::create_dir_all(dir.join("aa")).unwrap();
+        fs::write(dir.join("stray"), [0; 50]).unwrap();
+        fs::write(dir.join("aa").join("b.1.tmp"), [0; 50]).unwrap();
+
+        let hour_ago = SystemTime::now() - Duration::from_secs(60 * 60);
+        for (i, key) in ["aa1", "bb2", "cc3"].into_iter().enumerate() {
+            cache.put(key, &[0; 40]);
+            set_modified(&cache, key, hour_ago + Duration::from_secs(i as u64));
+        }
+        // Reading an entry makes it the most recently used one
+        assert!(cache.get("aa1", None).is_some());
+        cache.put("dd4", &[0; 40]);
+
+        assert!(cache.get("aa1", None).is_some());
+        assert!(cache.get("bb2", None).is_none());
+        assert!(cache.get("cc3", None).is_none());
+        assert!(cache.get("dd4", None).is_some());
+        assert_eq!(*cache.size.lock().unwrap(), Some(80));
+        assert!(dir.join("stray").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn expired_entries_are_deleted() {
+        let cache = cache("prune").with_max_age(Duration::from_secs(60));
+        let dir = cache.dir.clone().unwrap();
+        let hour_ago = SystemTime::now() - Duration::from_secs(60 * 60);
+        cache.put("aa1", b"old");
+        set_modified(&cache, "aa1", hour_ago);
+        cache.put("bb2", b"old");
+        set_modified(&cache, "bb2", hour_ago);
+
+        // Reading an expired entry deletes it
+        assert!(cache.get("aa1", Some(Duration::from_secs(60))).is_none());
+        assert!(!cache.path("aa1").unwrap().exists());
+
+        // The first write of a new run deletes the other ones
+        let cache = Cache {
+            pruned: Arc::new(AtomicBool::new(false)),
+            ..cache
+        };
+        cache.put("cc3", b"new");
+        assert!(!cache.path("bb2").unwrap().exists());
+        assert_eq!(
+            cache.get("cc3", Some(Duration::from_secs(60))),
+            Some(b"new".to_vec())
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
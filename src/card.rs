@@ -0,0 +1,176 @@
+use crate::{
+    DEFAULT_COLUMNS, MIN_SPRITE_COLUMNS, Opt, Pokemon, Stats,
+    color::{ColorDepth, RESET_BACKGROUND, RESET_FOREGROUND, type_color},
+    get_card_details, get_pokemon_sprite,
+    graphics::Graphics,
+    print_sprite, title_case, title_case_words,
+};
+
+// Widest the panel gets, in terminal columns
+const MAX_PANEL_COLUMNS: u32 = 40;
+
+// Narrowest panel that fits beside the sprite, narrower terminals get the panel below the sprite
+const MIN_PANEL_COLUMNS: u32 = 28;
+
+// Columns between the sprite and the panel
+const GAP_COLUMNS: u32 = 2;
+
+// Width of the labels column, fitting `Abilities`
+const LABEL_COLUMNS: usize = 10;
+
+// Base stat bars are relative to the highest base stat of any Pokemon, Blissey's HP
+const MAX_BASE_STAT: i64 = 255;
+
+// Partial blocks for the end of the base stat bars, in eighths of a cell
+const BAR_EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+/// Prints the sprite with a Pokedex entry beside it, or below it when the terminal is too narrow
+/// or the cursor can't be moved back up
+pub async fn print(
+    opt: &Opt,
+    graphics: &Graphics,
+    pokemon: &Pokemon,
+    with_sprites: bool,
+    sprite_bounds: Option<(u32, u32)>,
+) -> anyhow::Result<()> {
+    let card_details = get_card_details(opt, pokemon).await?;
+    let color = opt.color.depth();
+    let columns = sprite_bounds.map_or(DEFAULT_COLUMNS, |(columns, _)| columns);
+
+    let beside = with_sprites
+        && color.is_some()
+        && columns >= MIN_SPRITE_COLUMNS + GAP_COLUMNS + MIN_PANEL_COLUMNS;
+    let (sprite_columns, sprite_rows) = if with_sprites {
+        let sprite_image = get_pokemon_sprite(opt, pokemon).await?;
+        let bounds = if beside {
+            sprite_bounds.map(|(columns, rows)| (columns - GAP_COLUMNS - MIN_PANEL_COLUMNS, rows))
+        } else {
+            sprite_bounds
+        };
+        print_sprite(opt, graphics, sprite_image, bounds)?
+    } else {
+        (0, 0)
+    };
+
+    let panel_columns = if beside {
+        columns - sprite_columns - GAP_COLUMNS
+    } else {
+        columns
+    }
+    .min(MAX_PANEL_COLUMNS);
+
+    let mut lines = vec![
+        format!(
+            "No. {:03} {}",
+            pokemon.species_id,
+            bold(&pokemon.name.to_uppercase(), color)
+        ),
+        card_details.genus,
+        pokemon
+            .types
+            .iter()
+            .map(|pokemon_type| badge(pokemon_type, color))
+            .collect::<Vec<_>>()
+            .join(" "),
+        format!(
+            "{:LABEL_COLUMNS$}{}",
+            "Height",
+            tenths(card_details.height, "m")
+        ),
+        format!(
+            "{:LABEL_COLUMNS$}{}",
+            "Weight",
+            tenths(card_details.weight, "kg")
+        ),
+    ];
+    for (i, ability) in card_details.abilities.iter().enumerate() {
+        let label = if i == 0 { "Abilities" } else { "" };
+        let hidden = if ability.hidden { " (hidden)" } else { "" };
+        lines.push(format!(
+            "{label:LABEL_COLUMNS$}{}{hidden}",
+            title_case_words(&ability.name)
+        ));
+    }
+    lines.extend(stat_lines(&card_details.base_stats, panel_columns as usize));
+
+    if beside {
+        // The sprite leaves the cursor below it, so go back up to its first row
+        print!("\x1b[{sprite_rows}A");
+        for line in &lines {
+            println!("\x1b[{}C{line}", sprite_columns + GAP_COLUMNS);
+        }
+        for _ in lines.len()..sprite_rows as usize {
+            println!();
+        }
+    } else {
+        for line in &lines {
+            println!("{line}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws each base stat as a horizontal bar filling the rest of the panel, followed by the total
+fn stat_lines(base_stats: &Stats, panel_columns: usize) -> Vec<String> {
+    // The value takes 3 columns and a space
+    let bar_columns = panel_columns.saturating_sub(LABEL_COLUMNS + 4) as i64;
+    let stats = [
+        ("HP", base_stats.hp),
+        ("Attack", base_stats.attack),
+        ("Defense", base_stats.defense),
+        ("Sp. Atk", base_stats.special_attack),
+        ("Sp. Def", base_stats.special_defense),
+        ("Speed", base_stats.speed),
+    ];
+
+    let mut lines = stats
+        .iter()
+        .map(|&(label, value)| {
+            let eighths = value.min(MAX_BASE_STAT) * bar_columns * 8 / MAX_BASE_STAT;
+            format!(
+                "{label:LABEL_COLUMNS$}{value:>3} {}{}",
+                "█".repeat((eighths / 8) as usize),
+                BAR_EIGHTHS[(eighths % 8) as usize]
+            )
+        })
+        .collect::<Vec<_>>();
+    let total = stats.iter().map(|&(_, value)| value).sum::<i64>();
+    lines.push(format!("{:LABEL_COLUMNS$}{total:>3}", "Total"));
+    lines
+}
+
+/// Formats a measurement in tenths of the unit, like the heights and weights from PokeAPI
+fn tenths(value: i64, unit: &str) -> String {
+    format!("{}.{} {unit}", value / 10, value % 10)
+}
+
+fn bold(text: &str, color: Option<ColorDepth>) -> String {
+    if color.is_some() {
+        format!("\x1b[1m{text}\x1b[22m")
+    } else {
+        String::from(text)
+    }
+}
+
+/// Type name on a background of the type's color, or in brackets without colors
+fn badge(pokemon_type: &str, color: Option<ColorDepth>) -> String {
+    let name = title_case(pokemon_type);
+    match (type_color(pokemon_type), color) {
+        (Some(type_color), Some(depth)) => {
+            let [r, g, b] = type_color.0;
+            // Dark text stays readable on the lighter types like Electric and Ice
+            let foreground =
+                if 299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b) > 160_000 {
+                    30
+                } else {
+                    97
+                };
+            format!(
+                "{}\x1b[{foreground}m {name} {RESET_FOREGROUND}{RESET_BACKGROUND}",
+                depth.background(type_color)
+            )
+        }
+        _ => format!("[{name}]"),
+    }
+}
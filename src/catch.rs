@@ -0,0 +1,218 @@
+use std::{
+    io::{self, BufRead, Write},
+    time::Duration,
+};
+
+use clap::ValueEnum;
+use rand::Rng;
+
+use crate::{
+    Ball, Opt, get_pokemon, get_pokemon_details, get_pokemon_sprite, local_db::LocalDb,
+    new_graphics, print_sprite, terminal,
+};
+
+/// Rows reserved below the sprite for the prompt and the throws
+const RESERVED_ROWS: u32 = 8;
+
+const WOBBLE_DELAY: Duration = Duration::from_millis(700);
+
+pub async fn run(opt: &Opt) -> anyhow::Result<()> {
+    let local_db = LocalDb::new(&opt.data_dir).await?;
+    let pokemon = get_pokemon(opt).await?;
+    let details = get_pokemon_details(opt, &pokemon).await?;
+    if !opt.no_pokedex {
+        local_db
+            .record_encounter(pokemon.species_id, pokemon.shiny)
+            .await?;
+    }
+
+    let graphics = new_graphics(opt);
+    let sprite_bounds = terminal::size(opt.columns, opt.rows).map(|(columns, rows)| {
+        (
+            u32::from(columns),
+            u32::from(rows).saturating_sub(RESERVED_ROWS),
+        )
+    });
+    let sprite_image = get_pokemon_sprite(opt, &pokemon).await?;
+    print_sprite(opt, &graphics, sprite_image, sprite_bounds)?;
+
+    let name = pokemon.name.to_uppercase();
+    println!("Wild {name} appeared!");
+
+    let stdin = io::stdin();
+    loop {
+        print!("Throw a [1] Poké Ball, [2] Great Ball, [3] Ultra Ball or [r]un? ");
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        if stdin.lock().read_line(&mut input)? == 0 {
+            println!();
+            println!("Got away safely!");
+            return Ok(());
+        }
+        let ball = match input.trim().to_lowercase().as_str() {
+            "1" => Ball::Poke,
+            "2" => Ball::Great,
+            "3" => Ball::Ultra,
+            "r" | "run" => {
+                println!("Got away safely!");
+                return Ok(());
+            }
+            input => match Ball::from_str(input, true) {
+                Ok(ball) => ball,
+                Err(_) => continue,
+            },
+        };
+
+        let article = if matches!(ball, Ball::Ultra) {
+            "an"
+        } else {
+            "a"
+        };
+        print!("You threw {article} {}!", ball.name());
+        io::stdout().flush()?;
+        let shakes = throw(catch_value(details.capture_rate, ball), &mut rand::rng());
+        // The last shake check clicks the ball shut instead of wobbling it
+        for _ in 0..shakes.min(3) {
+            tokio::time::sleep(WOBBLE_DELAY).await;
+            print!(" ...wobble");
+            io::stdout().flush()?;
+        }
+        tokio::time::sleep(WOBBLE_DELAY).await;
+        println!();
+
+        match shakes {
+            4 => {
+                println!("Gotcha! {name} was caught!");
+                local_db.add_to_box(&pokemon, ball.name()).await?;
+                println!("{name} was sent to the box.");
+                return Ok(());
+            }
+            0 => println!("Oh no! The Pokémon broke free!"),
+            1 => println!("Aww! It appeared to be caught!"),
+            2 => println!("Aargh! Almost had it!"),
+            _ => println!("Gah! It was so close, too!"),
+        }
+    }
+}
+
+/// Throws a ball at a wild Pokemon using the catch formula from generation III onwards, returning
+/// the number of successful shake checks where 4 means the Pokemon was caught
+fn throw(catch_value: u32, rng: &mut impl Rng) -> u32 {
+    if catch_value >= 255 {
+        return 4;
+    }
+
+    let shake_probability = shake_probability(catch_value);
+    (0..4)
+        .take_while(|_| rng.random_range(0..65536) < shake_probability)
+        .count() as u32
+}
+
+/// Modified catch rate of a wild Pokemon at full health, at least 1
+fn catch_value(capture_rate: i64, ball: Ball) -> u32 {
+    // With full HP, `(3 * max_hp - 2 * hp) / (3 * max_hp)` is 1/3
+    (capture_rate as f64 * ball.catch_rate_modifier() / 3.0)
+        .floor()
+        .max(1.0) as u32
+}
+
+/// Threshold out of 65536 which each of the 4 shake checks must fall under
+fn shake_probability(catch_value: u32) -> u32 {
+    (1048560.0 / (16711680.0 / catch_value as f64).sqrt().sqrt()).floor() as u32
+}
+
+/// Lists the Pokemon caught so far
+pub async fn run_box(opt: &Opt) -> anyhow::Result<()> {
+    let local_db = LocalDb::new(&opt.data_dir).await?;
+    let caught = local_db.get_box().await?;
+    if caught.is_empty() {
+        println!("The box is empty, catch Pokémon with `pokemonsay catch`!");
+        return Ok(());
+    }
+
+    let name_width = caught
+        .iter()
+        .map(|pokemon| pokemon.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Pokémon".chars().count());
+    println!(
+        "{:>4}  {:name_width$}  {:11}  Caught",
+        "#", "Pokémon", "Ball"
+    );
+    for (i, pokemon) in caught.iter().enumerate() {
+        println!(
+            "{:>4}  {:name_width$}  {:11}  {}{}",
+            i + 1,
+            pokemon.name,
+            pokemon.ball,
+            pokemon
+                .caught_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            if pokemon.shiny { "  shiny" } else { "" }
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    #[test]
+    fn catch_value_applies_the_ball_at_full_health() {
+        assert_eq!(catch_value(45, Ball::Poke), 15);
+        assert_eq!(catch_value(45, Ball::Great), 22);
+        assert_eq!(catch_value(45, Ball::Ultra), 30);
+        assert_eq!(catch_value(255, Ball::Ultra), 170);
+    }
+
+    #[test]
+    fn catch_value_is_at_least_one() {
+        assert_eq!(catch_value(1, Ball::Poke), 1);
+        assert_eq!(catch_value(3, Ball::Poke), 1);
+    }
+
+    #[test]
+    fn shake_probability_matches_the_formula() {
+        assert_eq!(shake_probability(1), 16399);
+        assert_eq!(shake_probability(15), 32274);
+        assert_eq!(shake_probability(170), 59217);
+        assert_eq!(shake_probability(255), 65535);
+    }
+
+    #[test]
+    fn throw_always_catches_at_the_highest_catch_value() {
+        for seed in 0..100 {
+            assert_eq!(throw(255, &mut StdRng::seed_from_u64(seed)), 4);
+        }
+    }
+
+    #[test]
+    fn throw_mostly_breaks_free_on_the_first_shake_at_the_lowest_catch_value() {
+        // Each shake check succeeds with a chance of 16399 in 65536, about 1 in 4
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut shakes = [0; 5];
+        for _ in 0..1000 {
+            shakes[throw(1, &mut rng) as usize] += 1;
+        }
+        assert!((700..800).contains(&shakes[0]), "{shakes:?}");
+        assert!(shakes[4] < 20, "{shakes:?}");
+    }
+
+    #[test]
+    fn throw_catches_more_often_with_higher_catch_values() {
+        let catches = |catch_value| {
+            let mut rng = StdRng::seed_from_u64(7);
+            (0..1000)
+                .filter(|_| throw(catch_value, &mut rng) == 4)
+                .count()
+        };
+        assert!(catches(15) < catches(170));
+    }
+}
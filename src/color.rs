@@ -0,0 +1,125 @@
+use std::env;
+
+use image::Rgb;
+
+// Canonical color of each type, as used by the games and the official Pokedex
+const TYPE_COLORS: [(&str, [u8; 3]); 18] = [
+    ("normal", [168, 168, 120]),
+    ("fire", [240, 128, 48]),
+    ("water", [104, 144, 240]),
+    ("electric", [248, 208, 48]),
+    ("grass", [120, 200, 80]),
+    ("ice", [152, 216, 216]),
+    ("fighting", [192, 48, 40]),
+    ("poison", [160, 64, 160]),
+    ("ground", [224, 192, 104]),
+    ("flying", [168, 144, 240]),
+    ("psychic", [248, 88, 136]),
+    ("bug", [168, 184, 32]),
+    ("rock", [184, 160, 56]),
+    ("ghost", [112, 88, 152]),
+    ("dragon", [112, 56, 248]),
+    ("dark", [112, 88, 72]),
+    ("steel", [184, 184, 208]),
+    ("fairy", [238, 153, 172]),
+];
+
+// The 16 standard colors as xterm draws them, in the order of their escape sequences
+const ANSI_16_COLORS: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+pub const RESET_FOREGROUND: &str = "\x1b[39m";
+pub const RESET_BACKGROUND: &str = "\x1b[49m";
+
+pub fn type_color(pokemon_type: &str) -> Option<Rgb<u8>> {
+    TYPE_COLORS
+        .iter()
+        .find(|(name, _)| *name == pokemon_type)
+        .map(|&(_, color)| Rgb(color))
+}
+
+/// Colors supported by the terminal, which colors are approximated with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Detects the supported colors from `COLORTERM` and `TERM`, like most command line tools
+    pub fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            Self::TrueColor
+        } else if env::var("TERM").is_ok_and(|term| term.contains("256color")) {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Escape sequence setting the text color
+    pub fn foreground(self, color: Rgb<u8>) -> String {
+        self.escape_sequence(color, 38, 30, 90)
+    }
+
+    /// Escape sequence setting the color behind the text
+    pub fn background(self, color: Rgb<u8>) -> String {
+        self.escape_sequence(color, 48, 40, 100)
+    }
+
+    fn escape_sequence(
+        self,
+        Rgb([r, g, b]): Rgb<u8>,
+        extended: u8,
+        normal: u8,
+        bright: u8,
+    ) -> String {
+        match self {
+            Self::TrueColor => format!("\x1b[{extended};2;{r};{g};{b}m"),
+            Self::Ansi256 => {
+                // Nearest color of the 6x6x6 cube, whose levels are 0, 95, 135, 175, 215 and 255
+                let level = |channel: u8| match channel {
+                    0..48 => 0,
+                    48..115 => 1,
+                    _ => (channel - 35) / 40,
+                };
+                let index = 16 + 36 * level(r) + 6 * level(g) + level(b);
+                format!("\x1b[{extended};5;{index}m")
+            }
+            Self::Ansi16 => {
+                let distance = |[r2, g2, b2]: [u8; 3]| {
+                    [(r, r2), (g, g2), (b, b2)]
+                        .iter()
+                        .map(|&(a, b)| (i32::from(a) - i32::from(b)).pow(2))
+                        .sum::<i32>()
+                };
+                let index = (0..ANSI_16_COLORS.len())
+                    .min_by_key(|&i| distance(ANSI_16_COLORS[i]))
+                    .unwrap_or(0) as u8;
+                if index < 8 {
+                    format!("\x1b[{}m", normal + index)
+                } else {
+                    format!("\x1b[{}m", bright + index - 8)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,721 @@
+use std::{
+    any::TypeId,
+    env,
+    ffi::{OsStr, OsString},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::{Arg, ArgAction, ArgMatches, Command, parser::ValueSource};
+
+use crate::xdg_dir;
+
+// Table of the configuration file holding the profiles, e.g. `[profiles.motd]`
+const PROFILES_TABLE: &str = "profiles";
+
+// Options which choose the configuration itself, so they can't be set by it
+const RESERVED_OPTIONS: [&str; 4] = ["config", "profile", "help", "version"];
+
+// Prefix of the flags turning off other flags, e.g. `--no-card`
+const NEGATION_PREFIX: &str = "no-";
+
+/// Options set by the configuration file, which become the defaults of the command line options.
+/// Options of subcommands are set in tables named after them.
+///
+/// ```toml
+/// shiny-odds = 100
+///
+/// [quiz]
+/// rounds = 5
+///
+/// [profiles.motd]
+/// template-dir = "/home/ash/.config/pokemonsay/templates"
+/// count = 3
+/// ```
+pub struct Config {
+    path: PathBuf,
+    /// Whether the configuration file exists, as the default one is optional
+    found: bool,
+    options: Vec<ConfigOption>,
+    /// Flags turned off on the command line, by their subcommands and id
+    negated: Vec<(Vec<String>, String)>,
+    /// Looks up the environment variables of the options
+    env: fn(&OsStr) -> Option<OsString>,
+}
+
+struct ConfigOption {
+    /// Subcommands the option belongs to, e.g. `["quiz"]`, or none for the top-level options
+    subcommands: Vec<String>,
+    id: String,
+    values: Vec<String>,
+    /// Profile setting the option, or `None` for the top level of the configuration file
+    profile: Option<String>,
+}
+
+impl Config {
+    /// Loads the configuration file given by `--config`, or `pokemonsay/config.toml` in the XDG
+    /// config directory when it exists, applying the options of the `--profile` on top of the
+    /// top-level ones
+    pub fn load(command: &Command) -> anyhow::Result<Self> {
+        Self::load_from(command, env::args_os())
+    }
+
+    /// Like [`Config::load`], with the command line arguments given
+    pub fn load_from(
+        command: &Command,
+        args: impl IntoIterator<Item = impl Into<OsString> + Clone>,
+    ) -> anyhow::Result<Self> {
+        // Only `--config`, `--profile` and the negation flags are needed, the other options are
+        // parsed once the configuration has been applied
+        let matches = add_negations(command.clone())
+            .ignore_errors(true)
+            .try_get_matches_from(args)
+            .ok();
+        let config = matches
+            .as_ref()
+            .and_then(|matches| matches.get_one::<PathBuf>("config").cloned());
+        let profile = matches
+            .as_ref()
+            .and_then(|matches| matches.get_one::<String>("profile").cloned());
+        let negated = matches
+            .as_ref()
+            .map(|matches| negated_flags(command, matches, &[]))
+            .unwrap_or_default();
+
+        let (path, found) = match config {
+            Some(path) => (path, true),
+            None => {
+                let path = default_path()?;
+                let found = path.is_file();
+                (path, found)
+            }
+        };
+        if !found {
+            if let Some(profile) = profile {
+                return Err(anyhow::anyhow!(
+                    "Profile `{profile}` not found, {} doesn't exist!",
+                    path.display()
+                ));
+            }
+            return Ok(Self {
+                path,
+                found,
+                options: Vec::new(),
+                negated,
+                env: env_var,
+            });
+        }
+
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| anyhow::anyhow!("Failed to read config {}: {err}", path.display()))?;
+        let mut table = contents
+            .parse::<toml::Table>()
+            .map_err(|err| anyhow::anyhow!("Failed to parse config {}: {err}", path.display()))?;
+
+        let profiles = match table.remove(PROFILES_TABLE) {
+            Some(toml::Value::Table(profiles)) => profiles,
+            Some(_) => {
+                return Err(anyhow::anyhow!(
+                    "`{PROFILES_TABLE}` must be a table in config {}!",
+                    path.display()
+                ));
+            }
+            None => toml::Table::new(),
+        };
+        let mut options = parse_options(command, &path, table, None, &[])?;
+        if let Some(profile) = profile {
+            let Some(toml::Value::Table(profile_table)) = profiles.get(&profile) else {
+                return Err(anyhow::anyhow!(
+                    "Profile `{profile}` not found in config {}!",
+                    path.display()
+                ));
+            };
+            let profile_options =
+                parse_options(command, &path, profile_table.clone(), Some(profile), &[])?;
+            options.retain(|option| {
+                !profile_options.iter().any(|profile_option| {
+                    profile_option.subcommands == option.subcommands
+                        && profile_option.id == option.id
+                })
+            });
+            options.extend(profile_options);
+        }
+
+        Ok(Self {
+            path,
+            found,
+            options,
+            negated,
+            env: env_var,
+        })
+    }
+
+    /// Replaces the lookup of the environment variables, so that tests don't change the
+    /// environment of the process
+    #[cfg(test)]
+    fn with_env(mut self, env: fn(&OsStr) -> Option<OsString>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Sets the configured options as the defaults of the command, so that the command line and
+    /// the environment variables still take precedence, and adds the negation flags turning off
+    /// the configured flags
+    pub fn apply(&self, command: Command) -> Command {
+        let mut command = add_negations(command);
+        for option in &self.options {
+            if self
+                .negated
+                .contains(&(option.subcommands.clone(), option.id.clone()))
+            {
+                continue;
+            }
+            let values = option.values.clone();
+            command = mut_arg(command, &option.subcommands, &option.id, |arg| {
+                arg.default_values(values)
+            });
+        }
+        // clap reads the environment variables when the options are defined, so they're also set
+        // as the defaults here, over the configured values, to go through the lookup
+        for (subcommands, id, name) in env_args(&command, &[]) {
+            if self.negated.contains(&(subcommands.clone(), id.clone())) {
+                continue;
+            }
+            if let Some(value) = (self.env)(&name) {
+                command = mut_arg(command, &subcommands, &id, |arg| arg.default_value(value));
+            }
+        }
+        // The negation flags take precedence over the environment variables too
+        for (subcommands, id) in &self.negated {
+            command = mut_arg(command, subcommands, id, |arg| arg.env(None));
+        }
+        command
+    }
+
+    /// Checks the conflicts of the options set by the configuration file, which clap only checks
+    /// between the options given on the command line or by environment variables
+    pub fn validate(&self, command: &Command, matches: &ArgMatches) -> anyhow::Result<()> {
+        self.validate_subcommand(command, matches, &[])
+    }
+
+    fn validate_subcommand(
+        &self,
+        command: &Command,
+        matches: &ArgMatches,
+        subcommands: &[String],
+    ) -> anyhow::Result<()> {
+        for arg in configurable_args(command) {
+            if !self.is_set(arg, matches, subcommands) {
+                continue;
+            }
+            for other in command.get_arg_conflicts_with(arg) {
+                let configured = [arg, other].into_iter().any(|arg| {
+                    matches.value_source(arg.get_id().as_str()) == Some(ValueSource::DefaultValue)
+                });
+                if configured && self.is_set(other, matches, subcommands) {
+                    return Err(anyhow::anyhow!(
+                        "`--{}` ({}) conflicts with `--{}` ({})!",
+                        arg.get_long().unwrap_or(arg.get_id().as_str()),
+                        self.describe_source(arg, matches, subcommands),
+                        other.get_long().unwrap_or(other.get_id().as_str()),
+                        self.describe_source(other, matches, subcommands),
+                    ));
+                }
+            }
+        }
+
+        if let Some((name, matches)) = matches.subcommand()
+            && let Some(subcommand) = command.find_subcommand(name)
+        {
+            let subcommands = [subcommands, &[String::from(name)]].concat();
+            self.validate_subcommand(subcommand, matches, &subcommands)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the option is set by the command line, an environment variable or the
+    /// configuration file rather than left to its default, and isn't a flag turned off
+    fn is_set(&self, arg: &Arg, matches: &ArgMatches, subcommands: &[String]) -> bool {
+        let id = arg.get_id().as_str();
+        let set = match matches.value_source(id) {
+            Some(ValueSource::DefaultValue) => self.find(subcommands, id).is_some(),
+            Some(_) => true,
+            None => false,
+        };
+        set && matches.try_get_one::<bool>(id).ok().flatten() != Some(&false)
+    }
+
+    fn find(&self, subcommands: &[String], id: &str) -> Option<&ConfigOption> {
+        self.options
+            .iter()
+            .find(|option| option.subcommands == subcommands && option.id == id)
+    }
+
+    /// Where the value of the option comes from
+    fn describe_source(&self, arg: &Arg, matches: &ArgMatches, subcommands: &[String]) -> String {
+        match matches.value_source(arg.get_id().as_str()) {
+            Some(ValueSource::CommandLine) => String::from("command line"),
+            Some(ValueSource::EnvVariable) => match arg.get_env() {
+                Some(env) => format!("environment variable `{}`", env.to_string_lossy()),
+                None => String::from("environment variable"),
+            },
+            _ => self.source(subcommands, arg.get_id().as_str()),
+        }
+    }
+
+    /// Prints the value of every option and where it came from, in the format of the
+    /// configuration file. Options of subcommands are only given on the command line when the
+    /// subcommand runs, so their configured value is printed instead.
+    pub fn show(&self, command: &Command, matches: &ArgMatches) {
+        let found = if self.found { "" } else { " (not found)" };
+        println!("# Config: {}{found}", self.path.display());
+
+        for arg in configurable_args(command) {
+            let id = arg.get_id().as_str();
+            if matches.value_source(id).is_none() {
+                continue;
+            }
+            let values = matches
+                .get_raw(id)
+                .map(|values| {
+                    values
+                        .map(|value| value.to_string_lossy().into_owned())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            print_option(arg, &values, &self.describe_source(arg, matches, &[]));
+        }
+
+        self.show_subcommands(&self.apply(command.clone()), &[]);
+    }
+
+    fn show_subcommands(&self, command: &Command, subcommands: &[String]) {
+        for subcommand in command.get_subcommands() {
+            let subcommands = [subcommands, &[String::from(subcommand.get_name())]].concat();
+            let mut args = configurable_args(subcommand)
+                .filter(|arg| !arg.get_default_values().is_empty())
+                .peekable();
+            if args.peek().is_some() {
+                println!("\n[{}]", subcommands.join("."));
+            }
+            for arg in args {
+                let values = arg
+                    .get_default_values()
+                    .iter()
+                    .map(|value| value.to_string_lossy().into_owned())
+                    .collect::<Vec<_>>();
+                print_option(
+                    arg,
+                    &values,
+                    &self.source(&subcommands, arg.get_id().as_str()),
+                );
+            }
+            self.show_subcommands(subcommand, &subcommands);
+        }
+    }
+
+    /// Where the default value of the option comes from
+    fn source(&self, subcommands: &[String], id: &str) -> String {
+        if self
+            .negated
+            .contains(&(subcommands.to_vec(), String::from(id)))
+        {
+            return String::from("command line");
+        }
+        match self.find(subcommands, id) {
+            Some(ConfigOption {
+                profile: Some(profile),
+                ..
+            }) => format!("profile `{profile}`"),
+            Some(_) => String::from("config"),
+            None => String::from("default"),
+        }
+    }
+}
+
+fn default_path() -> anyhow::Result<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+        .map(|config_dir| config_dir.join(env!("CARGO_PKG_NAME")).join("config.toml"))
+        .ok_or_else(|| anyhow::anyhow!("Config directory not found!"))
+}
+
+/// Options of the command which can be set by the configuration file, named by their long flag
+fn configurable_args(command: &Command) -> impl Iterator<Item = &Arg> {
+    command.get_arguments().filter(|arg| {
+        arg.get_long().is_some() && !RESERVED_OPTIONS.contains(&arg.get_id().as_str())
+    })
+}
+
+fn parse_options(
+    command: &Command,
+    path: &Path,
+    table: toml::Table,
+    profile: Option<String>,
+    subcommands: &[String],
+) -> anyhow::Result<Vec<ConfigOption>> {
+    let location = match &profile {
+        Some(profile) => format!("profile `{profile}` of config {}", path.display()),
+        None => format!("config {}", path.display()),
+    };
+
+    let mut options = Vec::with_capacity(table.len());
+    for (key, value) in table {
+        if let toml::Value::Table(table) = value {
+            let subcommand = command
+                .find_subcommand(&key)
+                .ok_or_else(|| anyhow::anyhow!("Unknown subcommand `{key}` in {location}!"))?;
+            let subcommands = [subcommands, &[key]].concat();
+            options.extend(parse_options(
+                subcommand,
+                path,
+                table,
+                profile.clone(),
+                &subcommands,
+            )?);
+            continue;
+        }
+
+        let name = [subcommands, std::slice::from_ref(&key)].concat().join(".");
+        let arg = configurable_args(command)
+            .find(|arg| arg.get_long() == Some(key.as_str()))
+            .ok_or_else(|| anyhow::anyhow!("Unknown option `{name}` in {location}!"))?;
+        let values = match value {
+            toml::Value::Array(values) if matches!(arg.get_action(), ArgAction::Append) => {
+                values.iter().map(to_arg_value).collect::<Option<Vec<_>>>()
+            }
+            value => to_arg_value(&value).map(|value| vec![value]),
+        }
+        .ok_or_else(|| anyhow::anyhow!("Invalid value for `{name}` in {location}!"))?;
+        options.push(ConfigOption {
+            subcommands: subcommands.to_vec(),
+            id: String::from(arg.get_id().as_str()),
+            values,
+            profile: profile.clone(),
+        });
+    }
+    Ok(options)
+}
+
+fn env_var(name: &OsStr) -> Option<OsString> {
+    env::var_os(name)
+}
+
+/// Options of the command and its subcommands read from an environment variable, by their
+/// subcommands, id and variable
+fn env_args(command: &Command, subcommands: &[String]) -> Vec<(Vec<String>, String, OsString)> {
+    let mut args = configurable_args(command)
+        .filter_map(|arg| {
+            let name = arg.get_env()?;
+            Some((
+                subcommands.to_vec(),
+                String::from(arg.get_id().as_str()),
+                name.to_os_string(),
+            ))
+        })
+        .collect::<Vec<_>>();
+    for subcommand in command.get_subcommands() {
+        let subcommands = [subcommands, &[String::from(subcommand.get_name())]].concat();
+        args.extend(env_args(subcommand, &subcommands));
+    }
+    args
+}
+
+/// Modifies the option of the subcommand
+fn mut_arg(
+    command: Command,
+    subcommands: &[String],
+    id: &str,
+    f: impl FnOnce(Arg) -> Arg,
+) -> Command {
+    match subcommands.split_first() {
+        Some((subcommand, subcommands)) => {
+            command.mut_subcommand(subcommand, |command| mut_arg(command, subcommands, id, f))
+        }
+        None => command.mut_arg(id, f),
+    }
+}
+
+/// Long name of the flag turning off the flag, e.g. `no-card` for `--card` and `pokedex` for
+/// `--no-pokedex`, unless it's taken by another option of the command
+fn negation(command: &Command, arg: &Arg) -> Option<String> {
+    if !matches!(arg.get_action(), ArgAction::SetTrue) {
+        return None;
+    }
+    let long = arg.get_long()?;
+    let negation = match long.strip_prefix(NEGATION_PREFIX) {
+        Some(long) => String::from(long),
+        None => format!("{NEGATION_PREFIX}{long}"),
+    };
+    let taken = command.get_arguments().any(|other| {
+        other.get_long() == Some(negation.as_str()) || other.get_id() == negation.as_str()
+    });
+    (!taken).then_some(negation)
+}
+
+/// Adds the negation flags of the flags of the command and its subcommands, so that the flags set
+/// by the configuration file can be turned off. They're hidden to keep the help short.
+fn add_negations(mut command: Command) -> Command {
+    let negations = configurable_args(&command)
+        .filter_map(|arg| {
+            let negation = negation(&command, arg)?;
+            Some(
+                Arg::new(negation.clone())
+                    .long(negation)
+                    .action(ArgAction::SetTrue)
+                    .overrides_with(arg.get_id())
+                    .hide(true),
+            )
+        })
+        .collect::<Vec<_>>();
+    let subcommands = command
+        .get_subcommands()
+        .map(|subcommand| String::from(subcommand.get_name()))
+        .collect::<Vec<_>>();
+    for subcommand in subcommands {
+        command = command.mut_subcommand(subcommand, add_negations);
+    }
+    command.args(negations)
+}
+
+/// Flags of the command and its subcommand turned off by their negation flags
+fn negated_flags(
+    command: &Command,
+    matches: &ArgMatches,
+    subcommands: &[String],
+) -> Vec<(Vec<String>, String)> {
+    let mut negated = configurable_args(command)
+        .filter(|arg| {
+            negation(command, arg).is_some_and(|negation| {
+                matches!(matches.try_get_one::<bool>(&negation), Ok(Some(true)))
+            })
+        })
+        .map(|arg| (subcommands.to_vec(), String::from(arg.get_id().as_str())))
+        .collect::<Vec<_>>();
+    if let Some((name, matches)) = matches.subcommand()
+        && let Some(subcommand) = command.find_subcommand(name)
+    {
+        let subcommands = [subcommands, &[String::from(name)]].concat();
+        negated.extend(negated_flags(subcommand, matches, &subcommands));
+    }
+    negated
+}
+
+/// Converts a value of the configuration file to the text it would be given as on the command line
+fn to_arg_value(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(value) => Some(value.clone()),
+        toml::Value::Integer(value) => Some(value.to_string()),
+        toml::Value::Float(value) => Some(value.to_string()),
+        toml::Value::Boolean(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn print_option(arg: &Arg, values: &[String], source: &str) {
+    if let Some(long) = arg.get_long() {
+        println!("\n# {source}\n{long} = {}", to_toml(arg, values));
+    }
+}
+
+/// Formats the values of an option as it would be written in the configuration file
+fn to_toml(arg: &Arg, values: &[String]) -> String {
+    let value = |value: &String| {
+        if matches!(arg.get_action(), ArgAction::SetTrue | ArgAction::SetFalse) || is_number(arg) {
+            value.clone()
+        } else {
+            toml::Value::String(value.clone()).to_string()
+        }
+    };
+    match arg.get_action() {
+        ArgAction::Append => format!(
+            "[{}]",
+            values.iter().map(value).collect::<Vec<_>>().join(", ")
+        ),
+        _ => values.first().map(value).unwrap_or_default(),
+    }
+}
+
+/// Whether the option takes a number, which is written unquoted in the configuration file
+fn is_number(arg: &Arg) -> bool {
+    let type_id = arg.get_value_parser().type_id();
+    [
+        TypeId::of::<u8>(),
+        TypeId::of::<u16>(),
+        TypeId::of::<u32>(),
+        TypeId::of::<u64>(),
+        TypeId::of::<i64>(),
+        TypeId::of::<f32>(),
+        TypeId::of::<f64>(),
+    ]
+    .into_iter()
+    .any(|number_type_id| type_id == number_type_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ODDS_ENV: &str = "POKEMONSAY_TEST_SHINY_ODDS";
+
+    fn command() -> Command {
+        Command::new("pokemonsay")
+            .arg(
+                Arg::new("config")
+                    .long("config")
+                    .value_parser(clap::value_parser!(PathBuf)),
+            )
+            .arg(Arg::new("profile").long("profile"))
+            .arg(
+                Arg::new("shiny_odds")
+                    .long("shiny-odds")
+                    .env(ODDS_ENV)
+                    .value_parser(clap::value_parser!(u32))
+                    .default_value("4096"),
+            )
+            .arg(
+                Arg::new("count")
+                    .long("count")
+                    .value_parser(clap::value_parser!(u32)),
+            )
+            .arg(
+                Arg::new("card")
+                    .long("card")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("count"),
+            )
+            .subcommand(
+                Command::new("quiz").arg(
+                    Arg::new("rounds")
+                        .long("rounds")
+                        .value_parser(clap::value_parser!(u32))
+                        .default_value("10"),
+                ),
+            )
+    }
+
+    fn write_config(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("pokemonsay-{}-{name}.toml", std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn no_env(_: &OsStr) -> Option<OsString> {
+        None
+    }
+
+    fn get_matches(config_contents: &str, name: &str, args: &[&str]) -> (Config, ArgMatches) {
+        get_matches_with_env(config_contents, name, args, no_env)
+    }
+
+    fn get_matches_with_env(
+        config_contents: &str,
+        name: &str,
+        args: &[&str],
+        env: fn(&OsStr) -> Option<OsString>,
+    ) -> (Config, ArgMatches) {
+        let path = write_config(name, config_contents);
+        let args = ["pokemonsay", "--config", path.to_str().unwrap()]
+            .into_iter()
+            .chain(args.iter().copied())
+            .collect::<Vec<_>>();
+        let config = Config::load_from(&command(), &args).unwrap().with_env(env);
+        let matches = config.apply(command()).try_get_matches_from(&args).unwrap();
+        fs::remove_file(path).unwrap();
+        (config, matches)
+    }
+
+    #[test]
+    fn options_take_precedence_over_the_profile_top_level_and_defaults() {
+        const CONFIG: &str = "
+count = 2
+shiny-odds = 100
+
+[profiles.rare]
+shiny-odds = 8192
+";
+        let odds = |args: &[&str], env: fn(&OsStr) -> Option<OsString>| {
+            let (_, matches) = get_matches_with_env(CONFIG, "precedence", args, env);
+            *matches.get_one::<u32>("shiny_odds").unwrap()
+        };
+        let odds_env = |name: &OsStr| (name == ODDS_ENV).then(|| OsString::from("16"));
+
+        let (_, matches) = get_matches("", "precedence-empty", &[]);
+        assert_eq!(matches.get_one::<u32>("shiny_odds"), Some(&4096));
+        assert_eq!(odds(&[], no_env), 100);
+        assert_eq!(odds(&["--profile", "rare"], no_env), 8192);
+        assert_eq!(odds(&["--profile", "rare"], odds_env), 16);
+        assert_eq!(
+            odds(&["--profile", "rare", "--shiny-odds", "1"], odds_env),
+            1
+        );
+
+        // The top-level options not set by the profile still apply
+        let (config, matches) = get_matches(CONFIG, "precedence-count", &["--profile", "rare"]);
+        assert_eq!(matches.get_one::<u32>("count"), Some(&2));
+        assert_eq!(config.source(&[], "count"), "config");
+        assert_eq!(config.source(&[], "shiny_odds"), "profile `rare`");
+    }
+
+    #[test]
+    fn subcommand_options_are_set_by_their_tables() {
+        const CONFIG: &str = "
+[quiz]
+rounds = 5
+
+[profiles.short.quiz]
+rounds = 3
+";
+        let rounds = |args: &[&str]| {
+            let (_, matches) = get_matches(CONFIG, "subcommand", args);
+            let (_, quiz_matches) = matches.subcommand().unwrap();
+            *quiz_matches.get_one::<u32>("rounds").unwrap()
+        };
+
+        assert_eq!(rounds(&["quiz"]), 5);
+        assert_eq!(rounds(&["--profile", "short", "quiz"]), 3);
+        assert_eq!(rounds(&["--profile", "short", "quiz", "--rounds", "1"]), 1);
+    }
+
+    #[test]
+    fn negation_flags_turn_off_configured_flags() {
+        let card = |args: &[&str]| {
+            let (_, matches) = get_matches("card = true", "negation", args);
+            matches.get_flag("card")
+        };
+
+        assert!(card(&[]));
+        assert!(!card(&["--no-card"]));
+        assert!(card(&["--no-card", "--card"]));
+    }
+
+    #[test]
+    fn configured_options_conflicting_with_other_options_are_rejected() {
+        let validate = |contents: &str, args: &[&str]| {
+            let (config, matches) = get_matches(contents, "conflict", args);
+            config.validate(&command(), &matches)
+        };
+
+        assert!(validate("card = true", &[]).is_ok());
+        assert!(validate("card = true", &["--count", "3"]).is_err());
+        assert!(validate("count = 3", &["--card"]).is_err());
+        assert!(validate("card = false", &["--count", "3"]).is_ok());
+        assert!(validate("card = true", &["--no-card", "--count", "3"]).is_ok());
+    }
+
+    #[test]
+    fn unknown_options_and_subcommands_are_rejected() {
+        let load = |contents: &str| {
+            let path = write_config("unknown", contents);
+            let result = Config::load_from(
+                &command(),
+                ["pokemonsay", "--config", path.to_str().unwrap()],
+            );
+            fs::remove_file(path).unwrap();
+            result
+        };
+
+        assert!(load("shiny = true").is_err());
+        assert!(load("[battle]\nlevel = 5").is_err());
+        assert!(load("[quiz]\ncount = 5").is_err());
+    }
+}
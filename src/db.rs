@@ -1,21 +1,38 @@
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 
-use sea_orm::{ConnectOptions, Database, DbBackend, Statement, prelude::*};
+use sea_orm::{
+    ConnectOptions, Database, DbBackend, FromQueryResult, QueryOrder, Select, Statement, prelude::*,
+};
 
-use crate::{Pokemon, pokeapi_db::prelude::*};
+use crate::{
+    Ability, BattleDetails, CardDetails, Evolution, EvolutionChainLink, Move, Pokemon,
+    PokemonDetails, Species, TypeEfficacy,
+    pokeapi_db::{
+        pokemon_v2_item, pokemon_v2_language, pokemon_v2_pokemon, pokemon_v2_pokemonability,
+        pokemon_v2_pokemonevolution, pokemon_v2_pokemonspecies, pokemon_v2_pokemonspeciesname,
+        pokemon_v2_pokemonstat, pokemon_v2_pokemontype, prelude::*,
+    },
+};
 
 pub const DEFAULT_POKEMON_QUERY: &str = r#"
 SELECT
     "pokemon_v2_pokemon"."id",
-    "pokemon_v2_pokemon"."name",
-    "pokemon_v2_pokemon"."pokemon_species_id"
+    "pokemon_v2_pokemon"."pokemon_species_id",
+    "pokemon_v2_pokemonspeciesname"."name" AS "species_name",
+    JSON_EXTRACT("pokemon_v2_pokemonsprites"."sprites", '$.front_default') AS "sprite_url"
 FROM "pokemon_v2_pokemon"
 JOIN
     "pokemon_v2_pokemonsprites" ON "pokemon_v2_pokemonsprites"."pokemon_id" = "pokemon_v2_pokemon"."id"
+JOIN
+    "pokemon_v2_pokemonspeciesname" ON "pokemon_v2_pokemonspeciesname"."pokemon_species_id" = "pokemon_v2_pokemon"."pokemon_species_id"
+JOIN
+    "pokemon_v2_language" ON "pokemon_v2_language"."id" = "pokemon_v2_pokemonspeciesname"."language_id"
 WHERE 1=1
+    AND "pokemon_v2_language"."name" = 'en'
     AND JSON_EXTRACT("pokemon_v2_pokemonsprites"."sprites", '$.front_default') IS NOT NULL
+    AND "pokemon_v2_pokemon"."pokemon_species_id" NOT IN (SELECT "value" FROM JSON_EACH($1))
 ORDER BY RANDOM()
-LIMIT 1
+LIMIT $2
 "#;
 
 pub const DEFAULT_SPRITES_QUERY: &str = r#"
@@ -49,6 +66,178 @@ ORDER BY RANDOM()
 LIMIT 1
 "#;
 
+const SPECIES_QUERY: &str = r#"
+SELECT
+    "pokemon_v2_pokemonspecies"."id",
+    "pokemon_v2_pokemonspecies"."generation_id",
+    "pokemon_v2_pokemonspeciesname"."name",
+    COALESCE(JSON_EXTRACT("pokemon_v2_pokemonsprites"."sprites", '$.front_default'), '') AS "sprite_url"
+FROM "pokemon_v2_pokemonspecies"
+JOIN
+    "pokemon_v2_pokemonspeciesname" ON "pokemon_v2_pokemonspeciesname"."pokemon_species_id" = "pokemon_v2_pokemonspecies"."id"
+JOIN
+    "pokemon_v2_language" ON "pokemon_v2_language"."id" = "pokemon_v2_pokemonspeciesname"."language_id"
+JOIN
+    "pokemon_v2_pokemon" ON "pokemon_v2_pokemon"."pokemon_species_id" = "pokemon_v2_pokemonspecies"."id"
+JOIN
+    "pokemon_v2_pokemonsprites" ON "pokemon_v2_pokemonsprites"."pokemon_id" = "pokemon_v2_pokemon"."id"
+WHERE 1=1
+    AND "pokemon_v2_language"."name" = 'en'
+    AND "pokemon_v2_pokemon"."is_default" = 1
+ORDER BY "pokemon_v2_pokemonspecies"."id"
+"#;
+
+const BASE_STATS_QUERY: &str = r#"
+SELECT
+    "pokemon_v2_stat"."name",
+    "pokemon_v2_pokemonstat"."base_stat"
+FROM "pokemon_v2_pokemonstat"
+JOIN
+    "pokemon_v2_stat" ON "pokemon_v2_stat"."id" = "pokemon_v2_pokemonstat"."stat_id"
+WHERE 1=1
+    AND "pokemon_v2_pokemonstat"."pokemon_id" = $1
+"#;
+
+const DAMAGING_MOVES_QUERY: &str = r#"
+SELECT
+    COALESCE("pokemon_v2_movename"."name", "pokemon_v2_move"."name") AS "name",
+    "pokemon_v2_move"."power",
+    "pokemon_v2_move"."accuracy",
+    "pokemon_v2_move"."priority",
+    "pokemon_v2_type"."name" AS "move_type",
+    "pokemon_v2_movedamageclass"."name" AS "damage_class"
+FROM "pokemon_v2_move"
+JOIN
+    "pokemon_v2_type" ON "pokemon_v2_type"."id" = "pokemon_v2_move"."type_id"
+JOIN
+    "pokemon_v2_movedamageclass" ON "pokemon_v2_movedamageclass"."id" = "pokemon_v2_move"."move_damage_class_id"
+LEFT JOIN
+    "pokemon_v2_movename" ON "pokemon_v2_movename"."move_id" = "pokemon_v2_move"."id"
+    AND "pokemon_v2_movename"."language_id" = (
+        SELECT
+            "pokemon_v2_language"."id"
+        FROM "pokemon_v2_language"
+        WHERE 1=1
+            AND "pokemon_v2_language"."name" = 'en'
+        LIMIT 1
+    )
+WHERE 1=1
+    AND "pokemon_v2_move"."id" IN (
+        SELECT
+            "pokemon_v2_pokemonmove"."move_id"
+        FROM "pokemon_v2_pokemonmove"
+        WHERE 1=1
+            AND "pokemon_v2_pokemonmove"."pokemon_id" = $1
+    )
+    AND "pokemon_v2_move"."power" IS NOT NULL
+    AND "pokemon_v2_movedamageclass"."name" != 'status'
+ORDER BY "pokemon_v2_move"."id"
+"#;
+
+const TYPE_EFFICACY_QUERY: &str = r#"
+SELECT
+    "damage_type"."name" AS "damage_type",
+    "target_type"."name" AS "target_type",
+    "pokemon_v2_typeefficacy"."damage_factor"
+FROM "pokemon_v2_typeefficacy"
+JOIN
+    "pokemon_v2_type" AS "damage_type" ON "damage_type"."id" = "pokemon_v2_typeefficacy"."damage_type_id"
+JOIN
+    "pokemon_v2_type" AS "target_type" ON "target_type"."id" = "pokemon_v2_typeefficacy"."target_type_id"
+WHERE 1=1
+    AND "pokemon_v2_typeefficacy"."damage_factor" != 100
+"#;
+
+#[derive(Debug, FromQueryResult)]
+struct BaseStatQueryResult {
+    name: String,
+    base_stat: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct MoveQueryResult {
+    name: String,
+    power: i64,
+    accuracy: Option<i64>,
+    priority: i64,
+    move_type: String,
+    damage_class: String,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct TypeEfficacyQueryResult {
+    damage_type: String,
+    target_type: String,
+    damage_factor: i64,
+}
+
+const EVOLUTION_CHAIN_QUERY: &str = r#"
+SELECT
+    "pokemon_v2_pokemonspecies"."id",
+    "pokemon_v2_pokemonspecies"."generation_id",
+    "pokemon_v2_pokemonspecies"."evolves_from_species_id",
+    "pokemon_v2_pokemonspeciesname"."name",
+    COALESCE(JSON_EXTRACT("pokemon_v2_pokemonsprites"."sprites", '$.front_default'), '') AS "sprite_url"
+FROM "pokemon_v2_pokemonspecies"
+JOIN
+    "pokemon_v2_pokemonspeciesname" ON "pokemon_v2_pokemonspeciesname"."pokemon_species_id" = "pokemon_v2_pokemonspecies"."id"
+JOIN
+    "pokemon_v2_language" ON "pokemon_v2_language"."id" = "pokemon_v2_pokemonspeciesname"."language_id"
+JOIN
+    "pokemon_v2_pokemon" ON "pokemon_v2_pokemon"."pokemon_species_id" = "pokemon_v2_pokemonspecies"."id"
+JOIN
+    "pokemon_v2_pokemonsprites" ON "pokemon_v2_pokemonsprites"."pokemon_id" = "pokemon_v2_pokemon"."id"
+WHERE 1=1
+    AND "pokemon_v2_language"."name" = 'en'
+    AND "pokemon_v2_pokemon"."is_default" = 1
+    AND "pokemon_v2_pokemonspecies"."evolution_chain_id" = (
+        SELECT
+            "evolved_species"."evolution_chain_id"
+        FROM "pokemon_v2_pokemonspecies" AS "evolved_species"
+        WHERE 1=1
+            AND "evolved_species"."id" = $1
+    )
+ORDER BY "pokemon_v2_pokemonspecies"."id"
+"#;
+
+#[derive(Debug, FromQueryResult)]
+struct EvolutionChainQueryResult {
+    id: i64,
+    generation_id: i64,
+    evolves_from_species_id: Option<i64>,
+    name: String,
+    sprite_url: String,
+}
+
+/// Pokemon picked by the `--db-pokemon-query`, which may also return the English name of its
+/// species and its sprite to spare the queries fetching them
+#[derive(Debug, FromQueryResult)]
+struct PokemonQueryResult {
+    id: i64,
+    pokemon_species_id: Option<i64>,
+    species_name: Option<String>,
+    sprite_url: Option<String>,
+}
+
+impl From<pokemon_v2_pokemon::Model> for PokemonQueryResult {
+    fn from(pokemon: pokemon_v2_pokemon::Model) -> Self {
+        Self {
+            id: pokemon.id,
+            pokemon_species_id: pokemon.pokemon_species_id,
+            species_name: None,
+            sprite_url: None,
+        }
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct SpeciesQueryResult {
+    id: i64,
+    generation_id: i64,
+    name: String,
+    sprite_url: String,
+}
+
 pub struct Db {
     db: DatabaseConnection,
 }
@@ -89,55 +278,463 @@ impl Db {
         Ok(Self { db })
     }
 
+    /// Picks `count` random Pokemon with a single query
     pub async fn get_pokemon(
         &self,
         pokemon_query: impl AsRef<str>,
         species_name_query: impl AsRef<str>,
         sprites_query: impl AsRef<str>,
-    ) -> anyhow::Result<Pokemon> {
-        let pokemon = PokemonV2Pokemon::find()
-            .from_raw_sql(Statement::from_string(
+        excluded_species_ids: &[i64],
+        count: u32,
+    ) -> anyhow::Result<Vec<Pokemon>> {
+        let pokemon_results =
+            PokemonQueryResult::find_by_statement(Statement::from_sql_and_values(
                 DbBackend::Sqlite,
                 pokemon_query.as_ref(),
+                vec![
+                    serde_json::to_string(excluded_species_ids)?.into(),
+                    count.into(),
+                ],
             ))
+            .all(&self.db)
+            .await?;
+        if pokemon_results.is_empty() {
+            return Err(anyhow::anyhow!("Pokemon not found!"));
+        }
+
+        let mut species = PokemonV2Pokemonspecies::find()
+            .filter(
+                pokemon_v2_pokemonspecies::Column::Id.is_in(
+                    pokemon_results
+                        .iter()
+                        .filter_map(|pokemon_result| pokemon_result.pokemon_species_id),
+                ),
+            )
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|species| (species.id, species))
+            .collect::<HashMap<_, _>>();
+        let mut types = self
+            .get_types(
+                pokemon_results
+                    .iter()
+                    .map(|pokemon_result| pokemon_result.id),
+            )
+            .await?;
+
+        let mut pokemon = Vec::with_capacity(pokemon_results.len());
+        for pokemon_result in pokemon_results.into_iter().take(count as usize) {
+            let species = pokemon_result
+                .pokemon_species_id
+                .and_then(|species_id| species.remove(&species_id));
+            let types = types.remove(&pokemon_result.id).unwrap_or_default();
+            pokemon.push(
+                self.get_pokemon_name_and_sprite(
+                    pokemon_result,
+                    species,
+                    types,
+                    species_name_query.as_ref(),
+                    sprites_query.as_ref(),
+                )
+                .await?,
+            );
+        }
+        Ok(pokemon)
+    }
+
+    /// Gets the default Pokemon of a species, e.g. to follow an evolution
+    pub async fn get_pokemon_by_species(
+        &self,
+        species_id: i64,
+        species_name_query: impl AsRef<str>,
+        sprites_query: impl AsRef<str>,
+    ) -> anyhow::Result<Pokemon> {
+        // The default Pokemon of a species always comes first
+        let (pokemon, species) = PokemonV2Pokemon::find()
+            .filter(pokemon_v2_pokemon::Column::PokemonSpeciesId.eq(species_id))
+            .order_by_asc(pokemon_v2_pokemon::Column::Id)
+            .find_also_related(PokemonV2Pokemonspecies)
             .one(&self.db)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Pokemon not found!"))?;
 
-        let species_name = PokemonV2Pokemonspeciesname::find()
-            .from_raw_sql(Statement::from_sql_and_values(
-                DbBackend::Sqlite,
-                species_name_query.as_ref(),
-                vec![pokemon.pokemon_species_id.into()],
-            ))
+        let types = self
+            .get_types([pokemon.id])
+            .await?
+            .remove(&pokemon.id)
+            .unwrap_or_default();
+        self.get_pokemon_name_and_sprite(
+            pokemon.into(),
+            species,
+            types,
+            species_name_query,
+            sprites_query,
+        )
+        .await
+    }
+
+    /// Finds the id of a species by its Pokedex number, the name of one of its Pokemon or its name
+    /// in any language
+    pub async fn find_species_id(&self, name: &str) -> anyhow::Result<Option<i64>> {
+        if let Ok(species_id) = name.parse::<i64>() {
+            return Ok(PokemonV2Pokemonspecies::find_by_id(species_id)
+                .one(&self.db)
+                .await?
+                .map(|species| species.id));
+        }
+
+        if let Some(pokemon) = PokemonV2Pokemon::find()
+            .filter(pokemon_v2_pokemon::Column::Name.like(name))
             .one(&self.db)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Pokemon species name not found!"))?;
+        {
+            return Ok(pokemon.pokemon_species_id);
+        }
 
-        let sprites = PokemonV2Pokemonsprites::find()
-            .from_raw_sql(Statement::from_sql_and_values(
-                DbBackend::Sqlite,
-                sprites_query.as_ref(),
-                vec![pokemon.id.into()],
-            ))
+        Ok(PokemonV2Pokemonspeciesname::find()
+            .filter(pokemon_v2_pokemonspeciesname::Column::Name.like(name))
             .one(&self.db)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Pokemon sprites not found!"))?;
+            .and_then(|species_name| species_name.pokemon_species_id))
+    }
+
+    /// Returns the ways the species can evolve
+    pub async fn get_evolutions(&self, species_id: i64) -> anyhow::Result<Vec<Evolution>> {
+        self.load_evolutions(
+            PokemonV2Pokemonevolution::find()
+                .inner_join(PokemonV2Pokemonspecies)
+                .filter(pokemon_v2_pokemonspecies::Column::EvolvesFromSpeciesId.eq(species_id)),
+        )
+        .await
+    }
+
+    /// Returns every species in the evolution chain of the species, ordered by Pokedex number
+    pub async fn get_evolution_chain(
+        &self,
+        species_id: i64,
+    ) -> anyhow::Result<Vec<EvolutionChainLink>> {
+        let species = EvolutionChainQueryResult::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            EVOLUTION_CHAIN_QUERY,
+            vec![species_id.into()],
+        ))
+        .all(&self.db)
+        .await?;
+
+        let mut evolutions = self
+            .load_evolutions(
+                PokemonV2Pokemonevolution::find().filter(
+                    pokemon_v2_pokemonevolution::Column::EvolvedSpeciesId
+                        .is_in(species.iter().map(|species| species.id)),
+                ),
+            )
+            .await?;
+
+        Ok(species
+            .into_iter()
+            .map(|species| EvolutionChainLink {
+                evolutions: evolutions
+                    .extract_if(.., |evolution| evolution.species_id == species.id)
+                    .collect(),
+                evolves_from_species_id: species.evolves_from_species_id,
+                species: Species {
+                    id: species.id,
+                    generation: species.generation_id,
+                    name: species.name,
+                    sprite_url: species.sprite_url,
+                },
+            })
+            .collect())
+    }
+
+    /// Loads the evolutions selected by the query, along with their triggers and items
+    async fn load_evolutions(
+        &self,
+        query: Select<PokemonV2Pokemonevolution>,
+    ) -> anyhow::Result<Vec<Evolution>> {
+        let evolutions = query
+            .order_by_asc(pokemon_v2_pokemonevolution::Column::Id)
+            .find_also_related(PokemonV2Evolutiontrigger)
+            .all(&self.db)
+            .await?;
+
+        let items = PokemonV2Item::find()
+            .filter(
+                pokemon_v2_item::Column::Id.is_in(
+                    evolutions
+                        .iter()
+                        .filter_map(|(evolution, _)| evolution.evolution_item_id),
+                ),
+            )
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|item| (item.id, item.name))
+            .collect::<HashMap<_, _>>();
 
-        if sprites.sprites.is_empty() {
+        Ok(evolutions
+            .into_iter()
+            .filter_map(|(evolution, trigger)| {
+                Some(Evolution {
+                    species_id: evolution.evolved_species_id?,
+                    trigger: trigger?.name,
+                    min_level: evolution.min_level,
+                    min_happiness: evolution.min_happiness,
+                    item: evolution
+                        .evolution_item_id
+                        .and_then(|item_id| items.get(&item_id).cloned()),
+                })
+            })
+            .collect())
+    }
+
+    async fn get_pokemon_name_and_sprite(
+        &self,
+        pokemon: PokemonQueryResult,
+        species: Option<pokemon_v2_pokemonspecies::Model>,
+        types: Vec<String>,
+        species_name_query: impl AsRef<str>,
+        sprites_query: impl AsRef<str>,
+    ) -> anyhow::Result<Pokemon> {
+        let species = species.ok_or_else(|| anyhow::anyhow!("Pokemon species not found!"))?;
+
+        let name = match pokemon.species_name {
+            Some(name) => name,
+            None => {
+                PokemonV2Pokemonspeciesname::find()
+                    .from_raw_sql(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        species_name_query.as_ref(),
+                        vec![pokemon.pokemon_species_id.into()],
+                    ))
+                    .one(&self.db)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Pokemon species name not found!"))?
+                    .name
+            }
+        };
+
+        let sprite_url = match pokemon.sprite_url {
+            Some(sprite_url) => sprite_url,
+            None => {
+                PokemonV2Pokemonsprites::find()
+                    .from_raw_sql(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        sprites_query.as_ref(),
+                        vec![pokemon.id.into()],
+                    ))
+                    .one(&self.db)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Pokemon sprites not found!"))?
+                    .sprites
+            }
+        };
+
+        if sprite_url.is_empty() {
             return Err(anyhow::anyhow!("Pokemon sprite not found!"));
-        } else if let Ok(sprites_json) = serde_json::from_str::<serde_json::Value>(&sprites.sprites)
+        } else if let Ok(sprites_json) = serde_json::from_str::<serde_json::Value>(&sprite_url)
             && !sprites_json.is_string()
         {
             return Err(anyhow::anyhow!("Pokemon sprite not a string!"));
         }
 
         Ok(Pokemon {
-            name: species_name.name,
-            sprite_url: sprites.sprites,
+            id: pokemon.id,
+            species_id: pokemon
+                .pokemon_species_id
+                .ok_or_else(|| anyhow::anyhow!("Pokemon species not found!"))?,
+            name,
+            sprite_url,
+            types,
+            shiny: false,
+            female: false,
+            gender_rate: species
+                .gender_rate
+                .ok_or_else(|| anyhow::anyhow!("Pokemon gender rate not found!"))?,
+            has_gender_differences: species.has_gender_differences.unwrap_or_default(),
+        })
+    }
+
+    /// Returns the types of each of the Pokemon in slot order, keyed by Pokemon id
+    async fn get_types(
+        &self,
+        pokemon_ids: impl IntoIterator<Item = i64>,
+    ) -> anyhow::Result<HashMap<i64, Vec<String>>> {
+        let pokemon_types = PokemonV2Pokemontype::find()
+            .find_also_related(PokemonV2Type)
+            .filter(pokemon_v2_pokemontype::Column::PokemonId.is_in(pokemon_ids))
+            .order_by_asc(pokemon_v2_pokemontype::Column::Slot)
+            .all(&self.db)
+            .await?;
+
+        let mut types = HashMap::<_, Vec<_>>::new();
+        for (pokemon_type, type_model) in pokemon_types {
+            if let (Some(pokemon_id), Some(type_model)) = (pokemon_type.pokemon_id, type_model) {
+                types.entry(pokemon_id).or_default().push(type_model.name);
+            }
+        }
+        Ok(types)
+    }
+
+    pub async fn get_pokemon_details(&self, pokemon: &Pokemon) -> anyhow::Result<PokemonDetails> {
+        let (pokemon_model, species) = PokemonV2Pokemon::find_by_id(pokemon.id)
+            .find_also_related(PokemonV2Pokemonspecies)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Pokemon not found!"))?;
+        let species = species.ok_or_else(|| anyhow::anyhow!("Pokemon species not found!"))?;
+
+        let names = PokemonV2Pokemonspeciesname::find()
+            .filter(pokemon_v2_pokemonspeciesname::Column::PokemonSpeciesId.eq(pokemon.species_id))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|species_name| species_name.name)
+            .collect();
+
+        Ok(PokemonDetails {
+            names,
+            generation: species
+                .generation_id
+                .ok_or_else(|| anyhow::anyhow!("Pokemon generation not found!"))?,
+            capture_rate: species
+                .capture_rate
+                .ok_or_else(|| anyhow::anyhow!("Pokemon capture rate not found!"))?,
+            growth_rate: species
+                .growth_rate_id
+                .ok_or_else(|| anyhow::anyhow!("Pokemon growth rate not found!"))?,
+            base_experience: pokemon_model.base_experience,
+        })
+    }
+
+    /// Returns what the Pokedex shows about the Pokemon besides its name and types
+    pub async fn get_card_details(&self, pokemon: &Pokemon) -> anyhow::Result<CardDetails> {
+        let pokemon_model = PokemonV2Pokemon::find_by_id(pokemon.id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Pokemon not found!"))?;
+
+        let genus = PokemonV2Pokemonspeciesname::find()
+            .inner_join(PokemonV2Language)
+            .filter(pokemon_v2_pokemonspeciesname::Column::PokemonSpeciesId.eq(pokemon.species_id))
+            .filter(pokemon_v2_language::Column::Name.eq("en"))
+            .one(&self.db)
+            .await?
+            .map(|species_name| species_name.genus)
+            .unwrap_or_default();
+
+        let abilities = PokemonV2Pokemonability::find()
+            .filter(pokemon_v2_pokemonability::Column::PokemonId.eq(pokemon.id))
+            .order_by_asc(pokemon_v2_pokemonability::Column::Slot)
+            .find_also_related(PokemonV2Ability)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .filter_map(|(pokemon_ability, ability)| {
+                Some(Ability {
+                    name: ability?.name,
+                    hidden: pokemon_ability.is_hidden.unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        let base_stats = PokemonV2Pokemonstat::find()
+            .filter(pokemon_v2_pokemonstat::Column::PokemonId.eq(pokemon.id))
+            .find_also_related(PokemonV2Stat)
+            .all(&self.db)
+            .await?
+            .iter()
+            .filter_map(|(pokemon_stat, stat)| {
+                Some((stat.as_ref()?.name.as_str(), pokemon_stat.base_stat))
+            })
+            .collect();
+
+        Ok(CardDetails {
+            genus,
+            height: pokemon_model.height.unwrap_or_default(),
+            weight: pokemon_model.weight.unwrap_or_default(),
+            abilities,
+            base_stats,
         })
     }
 
+    /// Returns the base stats and the damaging moves of the Pokemon
+    pub async fn get_battle_details(&self, pokemon: &Pokemon) -> anyhow::Result<BattleDetails> {
+        let base_stats = BaseStatQueryResult::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            BASE_STATS_QUERY,
+            vec![pokemon.id.into()],
+        ))
+        .all(&self.db)
+        .await?;
+
+        let moves = MoveQueryResult::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            DAMAGING_MOVES_QUERY,
+            vec![pokemon.id.into()],
+        ))
+        .all(&self.db)
+        .await?;
+
+        Ok(BattleDetails {
+            base_stats: base_stats
+                .iter()
+                .map(|base_stat| (base_stat.name.as_str(), base_stat.base_stat))
+                .collect(),
+            moves: moves
+                .into_iter()
+                .map(|pokemon_move| Move {
+                    name: pokemon_move.name,
+                    power: pokemon_move.power,
+                    accuracy: pokemon_move.accuracy,
+                    priority: pokemon_move.priority,
+                    move_type: pokemon_move.move_type,
+                    damage_class: pokemon_move.damage_class,
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn get_type_efficacy(&self) -> anyhow::Result<TypeEfficacy> {
+        let type_efficacy = TypeEfficacyQueryResult::find_by_statement(Statement::from_string(
+            DbBackend::Sqlite,
+            TYPE_EFFICACY_QUERY,
+        ))
+        .all(&self.db)
+        .await?;
+
+        Ok(type_efficacy
+            .into_iter()
+            .map(|efficacy| {
+                (
+                    (efficacy.damage_type, efficacy.target_type),
+                    efficacy.damage_factor,
+                )
+            })
+            .collect())
+    }
+
+    /// Returns every species with its English name and default sprite, ordered by Pokedex number
+    pub async fn get_species(&self) -> anyhow::Result<Vec<Species>> {
+        let species = SpeciesQueryResult::find_by_statement(Statement::from_string(
+            DbBackend::Sqlite,
+            SPECIES_QUERY,
+        ))
+        .all(&self.db)
+        .await?;
+
+        Ok(species
+            .into_iter()
+            .map(|species| Species {
+                id: species.id,
+                generation: species.generation_id,
+                name: species.name,
+                sprite_url: species.sprite_url,
+            })
+            .collect())
+    }
+
     #[cfg(feature = "embed-sprites")]
     pub fn get_sprites(url: &str) -> anyhow::Result<bytes::Bytes> {
         include!(concat!(env!("OUT_DIR"), "/embed_sprites_paths"))
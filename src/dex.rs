@@ -0,0 +1,145 @@
+use std::collections::{BTreeMap, HashMap};
+
+use image::DynamicImage;
+
+use crate::{
+    CropMode, DexOpt, Effect, Opt, Species, get_species, get_sprite, graphics::Graphics,
+    local_db::LocalDb, new_graphics, print_sprite_grid, roman_numeral, terminal,
+};
+
+// Width of a Pokemon's cell in the sprites grid, in terminal columns
+const SPRITE_COLUMNS: u32 = 12;
+
+// Pokemon per group in the text grid, groups are separated by a space
+const GROUP_SIZE: usize = 10;
+
+pub async fn run(opt: &Opt, dex: &DexOpt) -> anyhow::Result<()> {
+    let local_db = LocalDb::new(&opt.data_dir).await?;
+    let seen = local_db.get_seen_species().await?;
+
+    let mut generations = BTreeMap::<i64, Vec<Species>>::new();
+    for species in get_species(opt).await? {
+        if dex.generations.is_empty() || dex.generations.contains(&species.generation) {
+            generations
+                .entry(species.generation)
+                .or_default()
+                .push(species);
+        }
+    }
+
+    let columns = terminal::size(opt.columns, opt.rows)
+        .map(|(columns, _)| u32::from(columns))
+        .unwrap_or(80);
+    let graphics = dex.sprites.then(|| new_graphics(opt));
+
+    let (mut total_seen, mut total) = (0, 0);
+    for (generation, species) in &generations {
+        let seen_count = species
+            .iter()
+            .filter(|species| seen.contains_key(&species.id))
+            .count();
+        let shiny_count = species
+            .iter()
+            .filter(|species| seen.get(&species.id) == Some(&true))
+            .count();
+        total_seen += seen_count;
+        total += species.len();
+
+        print!(
+            "Generation {}: {seen_count}/{} ({}%)",
+            roman_numeral(*generation),
+            species.len(),
+            seen_count * 100 / species.len()
+        );
+        if shiny_count > 0 {
+            print!(", {shiny_count} shiny");
+        }
+        println!();
+
+        match &graphics {
+            Some(graphics) => print_sprites_grid(opt, graphics, species, &seen, columns).await?,
+            None => print_text_grid(species, &seen, columns as usize),
+        }
+    }
+
+    if generations.len() > 1 {
+        println!(
+            "Total: {total_seen}/{total} ({}%)",
+            total_seen * 100 / total
+        );
+    }
+
+    Ok(())
+}
+
+fn print_text_grid(species: &[Species], seen: &HashMap<i64, bool>, columns: usize) {
+    let groups_per_line = ((columns + 1) / (GROUP_SIZE + 1)).max(1);
+    let marks = species
+        .iter()
+        .map(|species| match seen.get(&species.id) {
+            Some(true) => '★',
+            Some(false) => '●',
+            None => '·',
+        })
+        .collect::<Vec<_>>();
+
+    for line in marks.chunks(GROUP_SIZE * groups_per_line) {
+        let groups = line
+            .chunks(GROUP_SIZE)
+            .map(|group| group.iter().collect::<String>())
+            .collect::<Vec<_>>();
+        println!("{}", groups.join(" "));
+    }
+}
+
+async fn print_sprites_grid(
+    opt: &Opt,
+    graphics: &Graphics,
+    species: &[Species],
+    seen: &HashMap<i64, bool>,
+    columns: u32,
+) -> anyhow::Result<()> {
+    let mut sprites = Vec::with_capacity(species.len());
+    for species in species {
+        // A missing sprite leaves its cell empty rather than hiding the whole grid
+        let sprite_image = match get_sprite(opt, &species.sprite_url).await {
+            Ok(sprite_image) => sprite_image,
+            Err(err) => {
+                eprintln!(
+                    "Warning: Failed to get the sprite of #{}: {err}",
+                    species.id
+                );
+                sprites.push(DynamicImage::new_rgba8(1, 1));
+                continue;
+            }
+        };
+        let sprite_image =
+            crate::image_util::crop_transparent_pixels(&sprite_image, 0, 1, CropMode::Square);
+        sprites.push(if seen.contains_key(&species.id) {
+            sprite_image
+        } else {
+            crate::image_util::apply_effect(&sprite_image, Effect::Silhouette)
+        });
+    }
+
+    // Names of Pokemon not seen yet stay hidden, like in the games
+    let names = species
+        .iter()
+        .map(|species| {
+            if seen.contains_key(&species.id) {
+                species.name.as_str()
+            } else {
+                "???"
+            }
+        })
+        .collect::<Vec<_>>();
+    print_sprite_grid(
+        opt,
+        graphics,
+        &sprites,
+        &names,
+        SPRITE_COLUMNS,
+        columns,
+        None,
+    )
+}
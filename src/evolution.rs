@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use image::DynamicImage;
+
+use crate::{
+    CropMode, Effect, Evolution, EvolutionChainLink, Opt, Pokemon, get_evolution_chain,
+    get_pokemon_sprite, get_sprite, graphics::Graphics, print_sprite_grid, title_case_words,
+};
+
+// Width of a Pokemon's or an arrow's cell, in terminal columns, fitting labels like `Thunder Stone`
+const CELL_COLUMNS: u32 = 14;
+
+/// Prints every branch of the Pokemon's evolution chain on its own row, from the first species to
+/// the last, outlining the Pokemon itself
+pub async fn print_chain(
+    opt: &Opt,
+    graphics: &Graphics,
+    pokemon: &Pokemon,
+    with_sprites: bool,
+    columns: u32,
+) -> anyhow::Result<()> {
+    let chain = get_evolution_chain(opt, pokemon.species_id).await?;
+
+    let mut sprites = HashMap::new();
+    for branch in branches(&chain) {
+        let names = branch
+            .iter()
+            .map(|link| {
+                if link.species.id == pokemon.species_id {
+                    link.species.name.to_uppercase()
+                } else {
+                    link.species.name.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let labels = branch[1..]
+            .iter()
+            .map(|link| evolution_label(link.evolutions.first()))
+            .collect::<Vec<_>>();
+
+        if !with_sprites {
+            let mut line = names[0].clone();
+            for (label, name) in labels.iter().zip(&names[1..]) {
+                line.push_str(&format!(" —{label}→ {name}"));
+            }
+            println!("{line}");
+            continue;
+        }
+
+        for link in &branch {
+            if sprites.contains_key(&link.species.id) {
+                continue;
+            }
+            let mut sprite_image = if link.species.id == pokemon.species_id {
+                get_pokemon_sprite(opt, pokemon).await?
+            } else {
+                get_sprite(opt, &link.species.sprite_url).await?
+            };
+            // Square sprites keep the arrows between them evenly spaced
+            sprite_image =
+                crate::image_util::crop_transparent_pixels(&sprite_image, 0, 1, CropMode::Square);
+            for &effect in &opt.effects {
+                sprite_image = crate::image_util::apply_effect(&sprite_image, effect);
+            }
+            if link.species.id == pokemon.species_id {
+                sprite_image = crate::image_util::apply_effect(&sprite_image, Effect::Outline);
+            }
+            sprites.insert(link.species.id, sprite_image);
+        }
+
+        let branch_sprites = branch
+            .iter()
+            .map(|link| &sprites[&link.species.id])
+            .collect::<Vec<_>>();
+        let arrow = crate::image_util::arrow(
+            branch_sprites
+                .iter()
+                .map(|img| img.width())
+                .max()
+                .unwrap_or(0),
+            branch_sprites
+                .iter()
+                .map(|img| img.height())
+                .max()
+                .unwrap_or(0),
+        );
+
+        let mut cells = vec![branch_sprites[0].clone()];
+        let mut cell_names = vec![names[0].as_str()];
+        for ((sprite_image, name), label) in
+            branch_sprites[1..].iter().zip(&names[1..]).zip(&labels)
+        {
+            cells.extend([arrow.clone(), DynamicImage::clone(sprite_image)]);
+            cell_names.extend([label.as_str(), name.as_str()]);
+        }
+        print_sprite_grid(
+            opt,
+            graphics,
+            &cells,
+            &cell_names,
+            CELL_COLUMNS,
+            columns,
+            None,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Splits the chain into its branches, each going from a species without a pre-evolution to a
+/// species that doesn't evolve further
+fn branches(chain: &[EvolutionChainLink]) -> Vec<Vec<&EvolutionChainLink>> {
+    let mut branches = Vec::new();
+    for link in chain {
+        let is_first = !link
+            .evolves_from_species_id
+            .is_some_and(|evolves_from_species_id| {
+                chain
+                    .iter()
+                    .any(|link| link.species.id == evolves_from_species_id)
+            });
+        if is_first {
+            push_branches(chain, vec![link], &mut branches);
+        }
+    }
+    branches
+}
+
+fn push_branches<'a>(
+    chain: &'a [EvolutionChainLink],
+    branch: Vec<&'a EvolutionChainLink>,
+    branches: &mut Vec<Vec<&'a EvolutionChainLink>>,
+) {
+    let species_id = branch[branch.len() - 1].species.id;
+    let evolved = chain
+        .iter()
+        .filter(|link| link.evolves_from_species_id == Some(species_id))
+        .collect::<Vec<_>>();
+    if evolved.is_empty() {
+        branches.push(branch);
+        return;
+    }
+    for link in evolved {
+        let mut evolved_branch = branch.clone();
+        evolved_branch.push(link);
+        push_branches(chain, evolved_branch, branches);
+    }
+}
+
+/// Describes how to evolve, e.g. `Lv. 16`, `Thunder Stone`, `Friendship` or `Trade`
+fn evolution_label(evolution: Option<&Evolution>) -> String {
+    let Some(evolution) = evolution else {
+        return String::new();
+    };
+    if evolution.trigger == "trade" {
+        return String::from("Trade");
+    }
+    if let Some(item) = &evolution.item {
+        return title_case_words(item);
+    }
+    if let Some(min_level) = evolution.min_level {
+        return format!("Lv. {min_level}");
+    }
+    if evolution.min_happiness.is_some() {
+        return String::from("Friendship");
+    }
+    title_case_words(&evolution.trigger)
+}
@@ -0,0 +1,665 @@
+use std::{
+    env,
+    io::{self, Write},
+};
+
+use base64::{Engine, engine::general_purpose};
+use image::{DynamicImage, GenericImageView, ImageEncoder, Rgb, imageops::FilterType};
+
+use crate::{GraphicsProtocol, image_util, terminal};
+
+/// Pixels per terminal cell assumed when the terminal doesn't report its cell size, same as `viuer`
+pub const DEFAULT_CELL_SIZE: (u32, u32) = (6, 12);
+
+// Kitty requires payloads to be split into chunks of at most 4096 bytes
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+// Placeholder character used by the Kitty graphics protocol's unicode placement mode
+const KITTY_PLACEHOLDER: char = '\u{10EEEE}';
+
+// Characters used by the ASCII renderer, from lightest to darkest
+const ASCII_RAMP: &[u8] = b".:-=+*#%@";
+
+// GNU Screen truncates DCS strings longer than this
+const SCREEN_CHUNK_SIZE: usize = 768;
+
+/// Terminal multiplexer that graphics escape sequences need to be tunneled through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplexer {
+    None,
+    Tmux,
+    Screen,
+}
+
+impl Multiplexer {
+    pub fn detect() -> Self {
+        if env::var_os("TMUX").is_some() {
+            Self::Tmux
+        } else if env::var_os("STY").is_some()
+            || env::var("TERM").is_ok_and(|term| term.starts_with("screen"))
+        {
+            Self::Screen
+        } else {
+            Self::None
+        }
+    }
+
+    /// Wraps an escape sequence so that it is passed through to the outer terminal
+    fn wrap(&self, sequence: &str) -> String {
+        match self {
+            Self::None => String::from(sequence),
+            Self::Tmux => format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b")),
+            Self::Screen => sequence
+                .as_bytes()
+                .chunks(SCREEN_CHUNK_SIZE)
+                .map(|chunk| format!("\x1bP{}\x1b\\", String::from_utf8_lossy(chunk)))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Graphics {
+    protocol: GraphicsProtocol,
+    multiplexer: Multiplexer,
+    kitty_unicode_placeholders: bool,
+    cell_size: Option<(u32, u32)>,
+    background: Option<Rgb<u8>>,
+}
+
+impl Graphics {
+    pub fn new(
+        protocol: GraphicsProtocol,
+        kitty_unicode_placeholders: bool,
+        color: bool,
+        background: Option<Rgb<u8>>,
+    ) -> Self {
+        let multiplexer = Multiplexer::detect();
+        let protocol = match protocol {
+            // Detection itself writes escape sequences, so skip it when they aren't wanted
+            GraphicsProtocol::Auto if !color => GraphicsProtocol::Ascii,
+            GraphicsProtocol::Auto => detect_protocol(multiplexer),
+            protocol => protocol,
+        };
+        Self {
+            protocol,
+            multiplexer,
+            // Regular Kitty placements don't scroll along with the multiplexer's history
+            kitty_unicode_placeholders: kitty_unicode_placeholders
+                || multiplexer != Multiplexer::None,
+            // Half blocks fit two pixels in a cell, one above the other, and text renderers
+            // sample the image the same way
+            cell_size: match protocol {
+                GraphicsProtocol::Blocks | GraphicsProtocol::Ascii | GraphicsProtocol::Text => {
+                    Some((1, 2))
+                }
+                // Querying the terminal writes escape sequences, so skip it when they aren't wanted
+                _ if !color => None,
+                _ => terminal::cell_size(),
+            },
+            background,
+        }
+    }
+
+    /// The protocol used for printing, never `GraphicsProtocol::Auto`
+    pub fn protocol(&self) -> GraphicsProtocol {
+        self.protocol
+    }
+
+    /// Size of a terminal cell in pixels, if it could be determined
+    pub fn cell_size(&self) -> Option<(u32, u32)> {
+        self.cell_size
+    }
+
+    /// Prints the image, returning the dimensions of the printed image in terminal cells
+    pub fn print(&self, img: &DynamicImage, config: &viuer::Config) -> anyhow::Result<(u32, u32)> {
+        let (w, h) = find_best_fit(img.dimensions(), config.width, config.height);
+
+        let composited;
+        let img = match self.background {
+            Some(background) => {
+                composited = image_util::composite_background(img, background);
+                &composited
+            }
+            None => img,
+        };
+
+        let mut stdout = io::stdout().lock();
+        match self.protocol {
+            GraphicsProtocol::Auto | GraphicsProtocol::Blocks => {
+                let block_config = viuer::Config {
+                    use_kitty: false,
+                    use_iterm: false,
+                    use_sixel: false,
+                    ..*config
+                };
+                drop(stdout);
+                return Ok(viuer::print(img, &block_config)?);
+            }
+            GraphicsProtocol::Ascii => write_ascii(&mut stdout, img, (w, h), self.background)?,
+            GraphicsProtocol::Text => return Ok((0, 0)),
+            GraphicsProtocol::Kitty if self.kitty_unicode_placeholders => {
+                let id = rand::random_range(1..=u8::MAX);
+                self.print_kitty(&mut stdout, img, (w, h), &format!("U=1,i={id},q=2"))?;
+                write_kitty_placeholders(&mut stdout, id, (w, h))?;
+            }
+            GraphicsProtocol::Kitty => {
+                self.print_kitty(&mut stdout, img, (w, h), "C=1,q=2")?;
+                writeln!(stdout, "{}", "\n".repeat((h as usize).saturating_sub(1)))?;
+            }
+            GraphicsProtocol::Iterm => {
+                let mut png_bytes = Vec::new();
+                image::codecs::png::PngEncoder::new(&mut png_bytes).write_image(
+                    img.to_rgba8().as_raw(),
+                    img.width(),
+                    img.height(),
+                    image::ExtendedColorType::Rgba8,
+                )?;
+                let sequence = format!(
+                    "\x1b]1337;File=inline=1;preserveAspectRatio=1;size={};width={w};height={h}:{}\x07",
+                    png_bytes.len(),
+                    general_purpose::STANDARD.encode(&png_bytes)
+                );
+                write!(stdout, "{}", self.multiplexer.wrap(&sequence))?;
+                self.finish_line(&mut stdout, h)?;
+            }
+            GraphicsProtocol::Sixel => {
+                let (cell_width, cell_height) = self.cell_size.unwrap_or(DEFAULT_CELL_SIZE);
+                // The max 1000 width is an xterm bug workaround, same as `viuer`
+                let (sixel_width, sixel_height) = ((cell_width * w).min(1000), cell_height * h);
+                let resized = if img.dimensions() == (sixel_width, sixel_height) {
+                    img.clone()
+                } else {
+                    img.resize_exact(sixel_width, sixel_height, FilterType::Triangle)
+                };
+                let sequence = icy_sixel::sixel_string(
+                    resized.to_rgba8().as_raw(),
+                    resized.width() as i32,
+                    resized.height() as i32,
+                    icy_sixel::PixelFormat::RGBA8888,
+                    icy_sixel::DiffusionMethod::Auto,
+                    icy_sixel::MethodForLargest::Auto,
+                    icy_sixel::MethodForRep::Auto,
+                    icy_sixel::Quality::AUTO,
+                )
+                .map_err(|e| anyhow::anyhow!("Failed to encode Sixel image: {e}"))?;
+                write!(stdout, "{}", self.multiplexer.wrap(&sequence))?;
+                if self.multiplexer != Multiplexer::None {
+                    self.finish_line(&mut stdout, h)?;
+                }
+            }
+        }
+        stdout.flush()?;
+
+        Ok((w, h))
+    }
+
+    fn print_kitty(
+        &self,
+        stdout: &mut impl Write,
+        img: &DynamicImage,
+        (w, h): (u32, u32),
+        placement: &str,
+    ) -> anyhow::Result<()> {
+        let encoded = general_purpose::STANDARD.encode(img.to_rgba8().as_raw());
+        let mut chunks = encoded.as_bytes().chunks(KITTY_CHUNK_SIZE).peekable();
+        let mut first = true;
+        while let Some(chunk) = chunks.next() {
+            let more = u8::from(chunks.peek().is_some());
+            let chunk = String::from_utf8_lossy(chunk);
+            let sequence = if first {
+                format!(
+                    "\x1b_Ga=T,f=32,t=d,s={},v={},c={w},r={h},{placement},m={more};{chunk}\x1b\\",
+                    img.width(),
+                    img.height(),
+                )
+            } else {
+                format!("\x1b_Gm={more};{chunk}\x1b\\")
+            };
+            write!(stdout, "{}", self.multiplexer.wrap(&sequence))?;
+            first = false;
+        }
+        Ok(())
+    }
+
+    // The outer terminal moves its cursor below the image, but a multiplexer doesn't know about
+    // the passed through image so its cursor needs to be moved explicitly
+    fn finish_line(&self, stdout: &mut impl Write, h: u32) -> io::Result<()> {
+        if self.multiplexer == Multiplexer::None {
+            writeln!(stdout)
+        } else {
+            writeln!(stdout, "{}", "\n".repeat((h as usize).saturating_sub(1)))
+        }
+    }
+}
+
+// Multiplexers hide the outer terminal from `viuer`'s detection, but usually leak some of the
+// outer terminal's environment variables
+fn detect_protocol(multiplexer: Multiplexer) -> GraphicsProtocol {
+    if multiplexer != Multiplexer::None {
+        let term_program = env::var("LC_TERMINAL")
+            .or_else(|_| env::var("TERM_PROGRAM"))
+            .unwrap_or_default();
+        if env::var_os("KITTY_WINDOW_ID").is_some()
+            || env::var_os("GHOSTTY_RESOURCES_DIR").is_some()
+        {
+            return GraphicsProtocol::Kitty;
+        } else if env::var_os("WEZTERM_EXECUTABLE").is_some()
+            || ["iTerm", "WezTerm", "mintty", "rio"]
+                .iter()
+                .any(|name| term_program.contains(name))
+        {
+            return GraphicsProtocol::Iterm;
+        }
+    }
+
+    // Same order of precedence as `viuer`
+    if viuer::is_sixel_supported() {
+        GraphicsProtocol::Sixel
+    } else if viuer::is_iterm_supported() {
+        GraphicsProtocol::Iterm
+    } else if viuer::get_kitty_support() != viuer::KittySupport::None {
+        GraphicsProtocol::Kitty
+    } else {
+        GraphicsProtocol::Blocks
+    }
+}
+
+fn write_ascii(
+    stdout: &mut impl Write,
+    img: &DynamicImage,
+    (w, h): (u32, u32),
+    background: Option<Rgb<u8>>,
+) -> io::Result<()> {
+    let resized = img.resize_exact(w, h, FilterType::Triangle).to_rgba8();
+    for row in resized.rows() {
+        let line = row
+            .map(|pixel| {
+                // Pixels matching the background would be invisible on it, like transparent ones
+                if pixel[3] < u8::MAX / 2 || background.is_some_and(|bg| bg.0 == pixel.0[..3]) {
+                    return ' ';
+                }
+                // Darker pixels get denser characters, like the sprites' black outlines
+                let [r, g, b, _] = pixel.0.map(u32::from);
+                let luma = (299 * r + 587 * g + 114 * b) / 1000;
+                let index =
+                    (u8::MAX as u32 - luma) * (ASCII_RAMP.len() as u32 - 1) / u8::MAX as u32;
+                ASCII_RAMP[index as usize] as char
+            })
+            .collect::<String>();
+        writeln!(stdout, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+fn write_kitty_placeholders(stdout: &mut impl Write, id: u8, (w, h): (u32, u32)) -> io::Result<()> {
+    for row in 0..h as usize {
+        // The image id is encoded in the foreground color, the row and column in diacritics. The
+        // column of the following cells is inferred from the first one.
+        write!(
+            stdout,
+            "\x1b[38;5;{id}m{KITTY_PLACEHOLDER}{}{}{}\x1b[39m",
+            KITTY_DIACRITICS[row.min(KITTY_DIACRITICS.len() - 1)],
+            KITTY_DIACRITICS[0],
+            KITTY_PLACEHOLDER
+                .to_string()
+                .repeat((w as usize).saturating_sub(1))
+        )?;
+        writeln!(stdout)?;
+    }
+    Ok(())
+}
+
+/// Same as `viuer`'s fitting, returns dimensions in terminal cells where a cell is twice as high
+/// as it is wide
+pub fn find_best_fit(
+    (img_width, img_height): (u32, u32),
+    width: Option<u32>,
+    height: Option<u32>,
+) -> (u32, u32) {
+    match (width, height) {
+        (None, None) => {
+            let (term_w, term_h) = viuer::terminal_size();
+            let (w, h) = fit_dimensions(img_width, img_height, term_w as u32, term_h as u32);
+            let h = if h == term_h as u32 {
+                h.saturating_sub(1)
+            } else {
+                h
+            };
+            (w, h)
+        }
+        (Some(w), None) => fit_dimensions(img_width, img_height, w, img_height),
+        (None, Some(h)) => fit_dimensions(img_width, img_height, img_width, h),
+        (Some(w), Some(h)) => (w, h),
+    }
+}
+
+/// Shrinks dimensions in terminal cells to fit within the bounds, roughly preserving aspect ratio
+pub fn fit_within((width, height): (u32, u32), (max_width, max_height): (u32, u32)) -> (u32, u32) {
+    if width <= max_width && height <= max_height {
+        return (width, height);
+    }
+    let factor = (max_width as f32 / width as f32).min(max_height as f32 / height as f32);
+    (
+        ((width as f32 * factor) as u32).max(1),
+        ((height as f32 * factor) as u32).max(1),
+    )
+}
+
+pub fn fit_dimensions(width: u32, height: u32, bound_width: u32, bound_height: u32) -> (u32, u32) {
+    let bound_height = 2 * bound_height;
+
+    if width <= bound_width && height <= bound_height {
+        return (width.max(1), (height / 2 + height % 2).max(1));
+    }
+
+    if bound_width * height <= width * bound_height {
+        (bound_width, (height * bound_width / width / 2).max(1))
+    } else {
+        (
+            (width * bound_height / height).max(1),
+            (bound_height / 2).max(1),
+        )
+    }
+}
+
+// From https://sw.kovidgoyal.net/kitty/_downloads/1792bad15b12979994cd6ecc54c967a6/rowcolumn-diacritics.txt
+const KITTY_DIACRITICS: [char; 297] = [
+    '\u{305}',
+    '\u{30D}',
+    '\u{30E}',
+    '\u{310}',
+    '\u{312}',
+    '\u{33D}',
+    '\u{33E}',
+    '\u{33F}',
+    '\u{346}',
+    '\u{34A}',
+    '\u{34B}',
+    '\u{34C}',
+    '\u{350}',
+    '\u{351}',
+    '\u{352}',
+    '\u{357}',
+    '\u{35B}',
+    '\u{363}',
+    '\u{364}',
+    '\u{365}',
+    '\u{366}',
+    '\u{367}',
+    '\u{368}',
+    '\u{369}',
+    '\u{36A}',
+    '\u{36B}',
+    '\u{36C}',
+    '\u{36D}',
+    '\u{36E}',
+    '\u{36F}',
+    '\u{483}',
+    '\u{484}',
+    '\u{485}',
+    '\u{486}',
+    '\u{487}',
+    '\u{592}',
+    '\u{593}',
+    '\u{594}',
+    '\u{595}',
+    '\u{597}',
+    '\u{598}',
+    '\u{599}',
+    '\u{59C}',
+    '\u{59D}',
+    '\u{59E}',
+    '\u{59F}',
+    '\u{5A0}',
+    '\u{5A1}',
+    '\u{5A8}',
+    '\u{5A9}',
+    '\u{5AB}',
+    '\u{5AC}',
+    '\u{5AF}',
+    '\u{5C4}',
+    '\u{610}',
+    '\u{611}',
+    '\u{612}',
+    '\u{613}',
+    '\u{614}',
+    '\u{615}',
+    '\u{616}',
+    '\u{617}',
+    '\u{657}',
+    '\u{658}',
+    '\u{659}',
+    '\u{65A}',
+    '\u{65B}',
+    '\u{65D}',
+    '\u{65E}',
+    '\u{6D6}',
+    '\u{6D7}',
+    '\u{6D8}',
+    '\u{6D9}',
+    '\u{6DA}',
+    '\u{6DB}',
+    '\u{6DC}',
+    '\u{6DF}',
+    '\u{6E0}',
+    '\u{6E1}',
+    '\u{6E2}',
+    '\u{6E4}',
+    '\u{6E7}',
+    '\u{6E8}',
+    '\u{6EB}',
+    '\u{6EC}',
+    '\u{730}',
+    '\u{732}',
+    '\u{733}',
+    '\u{735}',
+    '\u{736}',
+    '\u{73A}',
+    '\u{73D}',
+    '\u{73F}',
+    '\u{740}',
+    '\u{741}',
+    '\u{743}',
+    '\u{745}',
+    '\u{747}',
+    '\u{749}',
+    '\u{74A}',
+    '\u{7EB}',
+    '\u{7EC}',
+    '\u{7ED}',
+    '\u{7EE}',
+    '\u{7EF}',
+    '\u{7F0}',
+    '\u{7F1}',
+    '\u{7F3}',
+    '\u{816}',
+    '\u{817}',
+    '\u{818}',
+    '\u{819}',
+    '\u{81B}',
+    '\u{81C}',
+    '\u{81D}',
+    '\u{81E}',
+    '\u{81F}',
+    '\u{820}',
+    '\u{821}',
+    '\u{822}',
+    '\u{823}',
+    '\u{825}',
+    '\u{826}',
+    '\u{827}',
+    '\u{829}',
+    '\u{82A}',
+    '\u{82B}',
+    '\u{82C}',
+    '\u{82D}',
+    '\u{951}',
+    '\u{953}',
+    '\u{954}',
+    '\u{F82}',
+    '\u{F83}',
+    '\u{F86}',
+    '\u{F87}',
+    '\u{135D}',
+    '\u{135E}',
+    '\u{135F}',
+    '\u{17DD}',
+    '\u{193A}',
+    '\u{1A17}',
+    '\u{1A75}',
+    '\u{1A76}',
+    '\u{1A77}',
+    '\u{1A78}',
+    '\u{1A79}',
+    '\u{1A7A}',
+    '\u{1A7B}',
+    '\u{1A7C}',
+    '\u{1B6B}',
+    '\u{1B6D}',
+    '\u{1B6E}',
+    '\u{1B6F}',
+    '\u{1B70}',
+    '\u{1B71}',
+    '\u{1B72}',
+    '\u{1B73}',
+    '\u{1CD0}',
+    '\u{1CD1}',
+    '\u{1CD2}',
+    '\u{1CDA}',
+    '\u{1CDB}',
+    '\u{1CE0}',
+    '\u{1DC0}',
+    '\u{1DC1}',
+    '\u{1DC3}',
+    '\u{1DC4}',
+    '\u{1DC5}',
+    '\u{1DC6}',
+    '\u{1DC7}',
+    '\u{1DC8}',
+    '\u{1DC9}',
+    '\u{1DCB}',
+    '\u{1DCC}',
+    '\u{1DD1}',
+    '\u{1DD2}',
+    '\u{1DD3}',
+    '\u{1DD4}',
+    '\u{1DD5}',
+    '\u{1DD6}',
+    '\u{1DD7}',
+    '\u{1DD8}',
+    '\u{1DD9}',
+    '\u{1DDA}',
+    '\u{1DDB}',
+    '\u{1DDC}',
+    '\u{1DDD}',
+    '\u{1DDE}',
+    '\u{1DDF}',
+    '\u{1DE0}',
+    '\u{1DE1}',
+    '\u{1DE2}',
+    '\u{1DE3}',
+    '\u{1DE4}',
+    '\u{1DE5}',
+    '\u{1DE6}',
+    '\u{1DFE}',
+    '\u{20D0}',
+    '\u{20D1}',
+    '\u{20D4}',
+    '\u{20D5}',
+    '\u{20D6}',
+    '\u{20D7}',
+    '\u{20DB}',
+    '\u{20DC}',
+    '\u{20E1}',
+    '\u{20E7}',
+    '\u{20E9}',
+    '\u{20F0}',
+    '\u{2CEF}',
+    '\u{2CF0}',
+    '\u{2CF1}',
+    '\u{2DE0}',
+    '\u{2DE1}',
+    '\u{2DE2}',
+    '\u{2DE3}',
+    '\u{2DE4}',
+    '\u{2DE5}',
+    '\u{2DE6}',
+    '\u{2DE7}',
+    '\u{2DE8}',
+    '\u{2DE9}',
+    '\u{2DEA}',
+    '\u{2DEB}',
+    '\u{2DEC}',
+    '\u{2DED}',
+    '\u{2DEE}',
+    '\u{2DEF}',
+    '\u{2DF0}',
+    '\u{2DF1}',
+    '\u{2DF2}',
+    '\u{2DF3}',
+    '\u{2DF4}',
+    '\u{2DF5}',
+    '\u{2DF6}',
+    '\u{2DF7}',
+    '\u{2DF8}',
+    '\u{2DF9}',
+    '\u{2DFA}',
+    '\u{2DFB}',
+    '\u{2DFC}',
+    '\u{2DFD}',
+    '\u{2DFE}',
+    '\u{2DFF}',
+    '\u{A66F}',
+    '\u{A67C}',
+    '\u{A67D}',
+    '\u{A6F0}',
+    '\u{A6F1}',
+    '\u{A8E0}',
+    '\u{A8E1}',
+    '\u{A8E2}',
+    '\u{A8E3}',
+    '\u{A8E4}',
+    '\u{A8E5}',
+    '\u{A8E6}',
+    '\u{A8E7}',
+    '\u{A8E8}',
+    '\u{A8E9}',
+    '\u{A8EA}',
+    '\u{A8EB}',
+    '\u{A8EC}',
+    '\u{A8ED}',
+    '\u{A8EE}',
+    '\u{A8EF}',
+    '\u{A8F0}',
+    '\u{A8F1}',
+    '\u{AAB0}',
+    '\u{AAB2}',
+    '\u{AAB3}',
+    '\u{AAB7}',
+    '\u{AAB8}',
+    '\u{AABE}',
+    '\u{AABF}',
+    '\u{AAC1}',
+    '\u{FE20}',
+    '\u{FE21}',
+    '\u{FE22}',
+    '\u{FE23}',
+    '\u{FE24}',
+    '\u{FE25}',
+    '\u{FE26}',
+    '\u{10A0F}',
+    '\u{10A38}',
+    '\u{1D185}',
+    '\u{1D186}',
+    '\u{1D187}',
+    '\u{1D188}',
+    '\u{1D189}',
+    '\u{1D1AA}',
+    '\u{1D1AB}',
+    '\u{1D1AC}',
+    '\u{1D1AD}',
+    '\u{1D242}',
+    '\u{1D243}',
+    '\u{1D244}',
+];
@@ -1,20 +1,108 @@
-use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Duration};
 
-use crate::Pokemon;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    Ability, BattleDetails, CacheMode, CardDetails, Evolution, EvolutionChainLink, Move, Pokemon,
+    PokemonDetails, Species, TypeEfficacy, cache::Cache,
+};
+
+// PokeAPI's data only changes with new games, so responses are kept for a week
+const GRAPHQL_CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+// The number of Pokemon is checked daily, as it's needed by every random encounter
+const POKEMON_COUNT_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Size of the sprite cache in MiB, fitting the default sprites of every Pokemon many times over
+pub const DEFAULT_SPRITE_CACHE_SIZE: u64 = 50;
+
+const BYTES_PER_MIB: u64 = 1024 * 1024;
 
 pub const DEFAULT_GRAPHQL_QUERY: &str = r#"
-  query ($random_offset: Int!) {
+  query ($random_offset: Int!, $excluded_species_ids: [Int!] = []) {
     pokemon(
       offset: $random_offset
       order_by: [{id: asc}]
       limit: 1
-      where: {pokemonsprites: {sprites: {_has_key: "front_default", _is_null: false}}}
+      where: {
+        pokemonsprites: {sprites: {_has_key: "front_default", _is_null: false}}
+        pokemon_species_id: {_nin: $excluded_species_ids}
+      }
+    ) {
+      id
+      pokemonspecy {
+        id
+        gender_rate
+        has_gender_differences
+        pokemonspeciesnames(where: {language: {name: {_eq: "en"}}}) {
+          name
+        }
+      }
+      pokemontypes(order_by: [{slot: asc}]) {
+        type {
+          name
+        }
+      }
+      pokemonsprites {
+        sprites(path: "front_default")
+      }
+    }
+  }
+"#;
+
+// Counts the Pokemon the default query picks from, once the excluded species are filtered out
+const POKEMON_COUNT_GRAPHQL_QUERY: &str = r#"
+  query ($excluded_species_ids: [Int!]!) {
+    pokemon_aggregate(
+      where: {
+        pokemonsprites: {sprites: {_has_key: "front_default", _is_null: false}}
+        pokemon_species_id: {_nin: $excluded_species_ids}
+      }
+    ) {
+      aggregate {
+        count
+      }
+    }
+  }
+"#;
+
+const POKEMON_DETAILS_GRAPHQL_QUERY: &str = r#"
+  query ($pokemon_id: Int!) {
+    pokemon(where: {id: {_eq: $pokemon_id}}) {
+      base_experience
+      pokemonspecy {
+        capture_rate
+        generation_id
+        growth_rate_id
+        pokemonspeciesnames {
+          name
+        }
+      }
+    }
+  }
+"#;
+
+const POKEMON_BY_SPECIES_GRAPHQL_QUERY: &str = r#"
+  query ($species_id: Int!) {
+    pokemon(
+      order_by: [{id: asc}]
+      limit: 1
+      where: {pokemon_species_id: {_eq: $species_id}}
     ) {
+      id
       pokemonspecy {
+        id
+        gender_rate
+        has_gender_differences
         pokemonspeciesnames(where: {language: {name: {_eq: "en"}}}) {
           name
         }
       }
+      pokemontypes(order_by: [{slot: asc}]) {
+        type {
+          name
+        }
+      }
       pokemonsprites {
         sprites(path: "front_default")
       }
@@ -22,68 +110,607 @@ pub const DEFAULT_GRAPHQL_QUERY: &str = r#"
   }
 "#;
 
+const FIND_SPECIES_GRAPHQL_QUERY: &str = r#"
+  query ($name: String!) {
+    pokemonspecies(
+      limit: 1
+      where: {
+        _or: [
+          {pokemons: {name: {_ilike: $name}}}
+          {pokemonspeciesnames: {name: {_ilike: $name}}}
+        ]
+      }
+    ) {
+      id
+    }
+  }
+"#;
+
+const EVOLUTIONS_GRAPHQL_QUERY: &str = r#"
+  query ($species_id: Int!) {
+    pokemonspecies(where: {evolves_from_species_id: {_eq: $species_id}}) {
+      id
+      pokemonevolutions {
+        min_level
+        min_happiness
+        item {
+          name
+        }
+        evolutiontrigger {
+          name
+        }
+      }
+    }
+  }
+"#;
+
+const EVOLUTION_CHAIN_GRAPHQL_QUERY: &str = r#"
+  query ($species_id: Int!) {
+    pokemonspecies(
+      order_by: [{id: asc}]
+      where: {evolutionchain: {pokemonspecies: {id: {_eq: $species_id}}}}
+    ) {
+      id
+      generation_id
+      evolves_from_species_id
+      pokemonspeciesnames(where: {language: {name: {_eq: "en"}}}) {
+        name
+      }
+      pokemons(where: {is_default: {_eq: true}}) {
+        pokemonsprites {
+          sprites(path: "front_default")
+        }
+      }
+      pokemonevolutions {
+        min_level
+        min_happiness
+        item {
+          name
+        }
+        evolutiontrigger {
+          name
+        }
+      }
+    }
+  }
+"#;
+
+const CARD_DETAILS_GRAPHQL_QUERY: &str = r#"
+  query ($pokemon_id: Int!) {
+    pokemon(where: {id: {_eq: $pokemon_id}}) {
+      height
+      weight
+      pokemonspecy {
+        pokemonspeciesnames(where: {language: {name: {_eq: "en"}}}) {
+          genus
+        }
+      }
+      pokemonabilities(order_by: [{slot: asc}]) {
+        is_hidden
+        ability {
+          name
+        }
+      }
+      pokemonstats {
+        base_stat
+        stat {
+          name
+        }
+      }
+    }
+  }
+"#;
+
+const BATTLE_DETAILS_GRAPHQL_QUERY: &str = r#"
+  query ($pokemon_id: Int!) {
+    pokemon(where: {id: {_eq: $pokemon_id}}) {
+      pokemonstats {
+        base_stat
+        stat {
+          name
+        }
+      }
+      pokemonmoves(
+        distinct_on: [move_id]
+        order_by: [{move_id: asc}]
+        where: {
+          move: {
+            power: {_is_null: false}
+            movedamageclass: {name: {_neq: "status"}}
+          }
+        }
+      ) {
+        move {
+          name
+          power
+          accuracy
+          priority
+          type {
+            name
+          }
+          movedamageclass {
+            name
+          }
+          movenames(where: {language: {name: {_eq: "en"}}}) {
+            name
+          }
+        }
+      }
+    }
+  }
+"#;
+
+const TYPE_EFFICACY_GRAPHQL_QUERY: &str = r#"
+  {
+    typeefficacy(where: {damage_factor: {_neq: 100}}) {
+      damage_factor
+      damage_type_id
+      target_type_id
+    }
+    type {
+      id
+      name
+    }
+  }
+"#;
+
+const SPECIES_GRAPHQL_QUERY: &str = r#"
+  {
+    pokemonspecies(order_by: [{id: asc}]) {
+      id
+      generation_id
+      pokemonspeciesnames(where: {language: {name: {_eq: "en"}}}) {
+        name
+      }
+      pokemons(where: {is_default: {_eq: true}}) {
+        pokemonsprites {
+          sprites(path: "front_default")
+        }
+      }
+    }
+  }
+"#;
+
 #[derive(Debug, Clone)]
 pub struct Http {
     client: reqwest::Client,
+    graphql_cache: Cache,
+    /// Sprites keyed by the hash of their contents, so that identical sprites are only stored once,
+    /// along with the hash of the contents of the sprite at each URL keyed by the hash of the URL.
+    /// Both count towards the size limit.
+    sprite_cache: Cache,
 }
 
 impl Http {
-    pub fn new() -> Self {
+    pub fn new(cache_mode: CacheMode) -> Self {
         Self {
             client: reqwest::Client::new(),
+            graphql_cache: Cache::new("graphql", cache_mode).with_max_age(GRAPHQL_CACHE_TTL),
+            sprite_cache: Cache::new("sprites", cache_mode)
+                .with_max_size(DEFAULT_SPRITE_CACHE_SIZE * BYTES_PER_MIB),
         }
     }
 
-    // This API has a rate limit of 200 calls per hour
-    // TODO: Add some caching to prevent repeated calls?
+    /// Limits the size of the sprite cache, in MiB
+    pub fn with_sprite_cache_size(mut self, max_size: u64) -> Self {
+        self.sprite_cache = self.sprite_cache.with_max_size(max_size * BYTES_PER_MIB);
+        self
+    }
+
+    // This API has a rate limit of 200 calls per hour, so responses are cached on disk
     const POKEAPI_GRAPHQL_API: &str = "https://graphql.pokeapi.co/v1beta2";
 
-    async fn get_last_pokemon(&self) -> anyhow::Result<i64> {
-        let body = serde_json::json!({
-          "query": r#"
+    async fn post_query<T: DeserializeOwned>(
+        &self,
+        graphql_query: &str,
+        variables: serde_json::Value,
+    ) -> anyhow::Result<T> {
+        self.post_query_with_ttl(graphql_query, variables, GRAPHQL_CACHE_TTL)
+            .await
+    }
+
+    async fn post_query_with_ttl<T: DeserializeOwned>(
+        &self,
+        graphql_query: &str,
+        variables: serde_json::Value,
+        ttl: Duration,
+    ) -> anyhow::Result<T> {
+        let body = serde_json::to_vec(&serde_json::json!({
+          "query": graphql_query,
+          "variables": variables
+        }))?;
+        let key = Cache::key(&body);
+        if let Some(query) = self
+            .graphql_cache
+            .get(&key, Some(ttl))
+            .and_then(|cached| serde_json::from_slice::<GraphQLQueryResponse<T>>(&cached).ok())
         {
-          pokemon(limit: 1, order_by: [{order: desc}]) {
-            id
-          }
+            return Ok(query.data);
         }
-      "#
-        });
+
         let res = self
             .client
             .post(Self::POKEAPI_GRAPHQL_API)
-            .body(serde_json::to_vec(&body)?)
+            .body(body)
             .send()
             .await?;
-        let query: GraphQLQueryResponse<PokemonQueryResponse<LastPokemonQueryResponseFields>> =
-            res.json().await?;
-        Ok(query.data.pokemon.0.id)
-    }
-
-    pub async fn get_pokemon(&self, graphql_query: impl AsRef<str>) -> anyhow::Result<Pokemon> {
-        let last_pokemon = self.get_last_pokemon().await?;
-        let random_offset = rand::random_range(0..=last_pokemon);
-        let body = serde_json::json!({
-          "query": graphql_query.as_ref(),
-          "variables": { "random_offset": random_offset }
-        });
-        let res = self
-            .client
-            .post(Self::POKEAPI_GRAPHQL_API)
-            .body(serde_json::to_vec(&body)?)
-            .send()
+        let response = res.bytes().await?;
+        let query: GraphQLQueryResponse<T> = serde_json::from_slice(&response)?;
+        self.graphql_cache.put(&key, &response);
+        Ok(query.data)
+    }
+
+    /// Sends the query once for each of the variables in a single request, skipping the ones whose
+    /// responses are cached
+    async fn post_batch<T: DeserializeOwned>(
+        &self,
+        graphql_query: &str,
+        variables: Vec<serde_json::Value>,
+    ) -> anyhow::Result<Vec<T>> {
+        let bodies = variables
+            .into_iter()
+            .map(|variables| {
+                serde_json::json!({
+                  "query": graphql_query,
+                  "variables": variables
+                })
+            })
+            .collect::<Vec<_>>();
+        let keys = bodies
+            .iter()
+            .map(|body| Ok(Cache::key(&serde_json::to_vec(body)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut queries = keys
+            .iter()
+            .map(|key| {
+                self.graphql_cache
+                    .get(key, Some(GRAPHQL_CACHE_TTL))
+                    .and_then(|cached| {
+                        serde_json::from_slice::<GraphQLQueryResponse<T>>(&cached).ok()
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let missing = (0..queries.len())
+            .filter(|&i| queries[i].is_none())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            let body = missing.iter().map(|&i| &bodies[i]).collect::<Vec<_>>();
+            let res = self
+                .client
+                .post(Self::POKEAPI_GRAPHQL_API)
+                .body(serde_json::to_vec(&body)?)
+                .send()
+                .await?;
+            let responses: Vec<serde_json::Value> = res.json().await?;
+            for (&i, response) in missing.iter().zip(responses) {
+                let response = serde_json::to_vec(&response)?;
+                queries[i] = Some(serde_json::from_slice(&response)?);
+                self.graphql_cache.put(&keys[i], &response);
+            }
+        }
+
+        queries
+            .into_iter()
+            .map(|query| {
+                query
+                    .map(|query| query.data)
+                    .ok_or_else(|| anyhow::anyhow!("Missing response in batch!"))
+            })
+            .collect()
+    }
+
+    /// Counts the Pokemon which don't belong to the excluded species
+    async fn get_pokemon_count(&self, excluded_species_ids: &[i64]) -> anyhow::Result<i64> {
+        let query: PokemonAggregateQueryResponse = self
+            .post_query_with_ttl(
+                POKEMON_COUNT_GRAPHQL_QUERY,
+                serde_json::json!({ "excluded_species_ids": excluded_species_ids }),
+                POKEMON_COUNT_CACHE_TTL,
+            )
+            .await?;
+        Ok(query.pokemon_aggregate.aggregate.count)
+    }
+
+    /// Picks `count` random Pokemon, batching the queries for every Pokemon into a single request
+    pub async fn get_pokemon(
+        &self,
+        graphql_query: impl AsRef<str>,
+        excluded_species_ids: &[i64],
+        count: u32,
+    ) -> anyhow::Result<Vec<Pokemon>> {
+        // The offsets range over the Pokemon the query filters, which aren't numbered
+        // contiguously and exclude the species recently encountered, so they have to be counted
+        let offsets = self.get_pokemon_count(excluded_species_ids).await? as usize;
+        if offsets == 0 {
+            return Err(anyhow::anyhow!("Pokemon not found!"));
+        }
+        let random_offsets =
+            rand::seq::index::sample(&mut rand::rng(), offsets, (count as usize).min(offsets));
+        let variables = random_offsets
+            .into_iter()
+            .map(|random_offset| {
+                let mut variables = serde_json::json!({ "random_offset": random_offset });
+                // Only sent when needed so custom queries don't have to declare the variable
+                if !excluded_species_ids.is_empty() {
+                    variables["excluded_species_ids"] = serde_json::json!(excluded_species_ids);
+                }
+                variables
+            })
+            .collect::<Vec<_>>();
+
+        let queries: Vec<PokemonQueryResponse<PokemonSpecyAndSpritesQueryResponseFields>> =
+            if let [variables] = &variables[..] {
+                vec![
+                    self.post_query(graphql_query.as_ref(), variables.clone())
+                        .await?,
+                ]
+            } else {
+                self.post_batch(graphql_query.as_ref(), variables).await?
+            };
+        Ok(queries
+            .into_iter()
+            .map(|query| query.pokemon.0.into())
+            .collect())
+    }
+
+    /// Gets the default Pokemon of a species, e.g. to follow an evolution
+    pub async fn get_pokemon_by_species(&self, species_id: i64) -> anyhow::Result<Pokemon> {
+        let query: PokemonQueryResponse<PokemonSpecyAndSpritesQueryResponseFields> = self
+            .post_query(
+                POKEMON_BY_SPECIES_GRAPHQL_QUERY,
+                serde_json::json!({ "species_id": species_id }),
+            )
             .await?;
-        let query: GraphQLQueryResponse<
-            PokemonQueryResponse<PokemonSpecyAndSpritesQueryResponseFields>,
-        > = res.json().await?;
-        Ok(Pokemon {
-            name: query.data.pokemon.0.pokemonspecy.pokemonspeciesnames.0.name,
-            sprite_url: query.data.pokemon.0.pokemonsprites.0.sprites,
+        Ok(query.pokemon.0.into())
+    }
+
+    /// Finds the id of a species by its Pokedex number, the name of one of its Pokemon or its name
+    /// in any language
+    pub async fn find_species_id(&self, name: &str) -> anyhow::Result<Option<i64>> {
+        if let Ok(species_id) = name.parse() {
+            return Ok(Some(species_id));
+        }
+
+        let query: PokemonSpeciesQueryResponse<IdQueryResponseFields> = self
+            .post_query(
+                FIND_SPECIES_GRAPHQL_QUERY,
+                serde_json::json!({ "name": name }),
+            )
+            .await?;
+        Ok(query
+            .pokemonspecies
+            .into_iter()
+            .next()
+            .map(|species| species.id))
+    }
+
+    /// Returns the ways the species can evolve
+    pub async fn get_evolutions(&self, species_id: i64) -> anyhow::Result<Vec<Evolution>> {
+        let query: PokemonSpeciesQueryResponse<EvolvedSpeciesQueryResponseFields> = self
+            .post_query(
+                EVOLUTIONS_GRAPHQL_QUERY,
+                serde_json::json!({ "species_id": species_id }),
+            )
+            .await?;
+        Ok(query
+            .pokemonspecies
+            .into_iter()
+            .flat_map(|species| {
+                species
+                    .pokemonevolutions
+                    .into_iter()
+                    .map(move |evolution| evolution.into_evolution(species.id))
+            })
+            .collect())
+    }
+
+    pub async fn get_evolution_chain(
+        &self,
+        species_id: i64,
+    ) -> anyhow::Result<Vec<EvolutionChainLink>> {
+        let query: PokemonSpeciesQueryResponse<EvolutionChainSpeciesQueryResponseFields> = self
+            .post_query(
+                EVOLUTION_CHAIN_GRAPHQL_QUERY,
+                serde_json::json!({ "species_id": species_id }),
+            )
+            .await?;
+        Ok(query
+            .pokemonspecies
+            .into_iter()
+            .filter_map(|species| {
+                Some(EvolutionChainLink {
+                    evolutions: species
+                        .pokemonevolutions
+                        .into_iter()
+                        .map(|evolution| evolution.into_evolution(species.id))
+                        .collect(),
+                    evolves_from_species_id: species.evolves_from_species_id,
+                    species: Species {
+                        id: species.id,
+                        generation: species.generation_id,
+                        name: species.pokemonspeciesnames.into_iter().next()?.name,
+                        sprite_url: species
+                            .pokemons
+                            .into_iter()
+                            .next()?
+                            .pokemonsprites
+                            .into_iter()
+                            .next()?
+                            .sprites
+                            .unwrap_or_default(),
+                    },
+                })
+            })
+            .collect())
+    }
+
+    pub async fn get_pokemon_details(&self, pokemon: &Pokemon) -> anyhow::Result<PokemonDetails> {
+        let query: PokemonQueryResponse<PokemonDetailsQueryResponseFields> = self
+            .post_query(
+                POKEMON_DETAILS_GRAPHQL_QUERY,
+                serde_json::json!({ "pokemon_id": pokemon.id }),
+            )
+            .await?;
+        let (details,) = query.pokemon;
+        Ok(PokemonDetails {
+            names: details
+                .pokemonspecy
+                .pokemonspeciesnames
+                .into_iter()
+                .map(|species_name| species_name.name)
+                .collect(),
+            generation: details.pokemonspecy.generation_id,
+            capture_rate: details.pokemonspecy.capture_rate,
+            growth_rate: details.pokemonspecy.growth_rate_id,
+            base_experience: details.base_experience,
+        })
+    }
+
+    /// Returns what the Pokedex shows about the Pokemon besides its name and types
+    pub async fn get_card_details(&self, pokemon: &Pokemon) -> anyhow::Result<CardDetails> {
+        let query: PokemonQueryResponse<CardDetailsQueryResponseFields> = self
+            .post_query(
+                CARD_DETAILS_GRAPHQL_QUERY,
+                serde_json::json!({ "pokemon_id": pokemon.id }),
+            )
+            .await?;
+        let (details,) = query.pokemon;
+        Ok(CardDetails {
+            genus: details
+                .pokemonspecy
+                .pokemonspeciesnames
+                .into_iter()
+                .next()
+                .map(|species_name| species_name.genus)
+                .unwrap_or_default(),
+            height: details.height.unwrap_or_default(),
+            weight: details.weight.unwrap_or_default(),
+            abilities: details
+                .pokemonabilities
+                .into_iter()
+                .map(|pokemon_ability| Ability {
+                    name: pokemon_ability.ability.name,
+                    hidden: pokemon_ability.is_hidden,
+                })
+                .collect(),
+            base_stats: details
+                .pokemonstats
+                .iter()
+                .map(|pokemon_stat| (pokemon_stat.stat.name.as_str(), pokemon_stat.base_stat))
+                .collect(),
         })
     }
 
+    /// Returns the base stats and the damaging moves of the Pokemon
+    pub async fn get_battle_details(&self, pokemon: &Pokemon) -> anyhow::Result<BattleDetails> {
+        let query: PokemonQueryResponse<BattleDetailsQueryResponseFields> = self
+            .post_query(
+                BATTLE_DETAILS_GRAPHQL_QUERY,
+                serde_json::json!({ "pokemon_id": pokemon.id }),
+            )
+            .await?;
+        let (details,) = query.pokemon;
+        Ok(BattleDetails {
+            base_stats: details
+                .pokemonstats
+                .iter()
+                .map(|pokemon_stat| (pokemon_stat.stat.name.as_str(), pokemon_stat.base_stat))
+                .collect(),
+            moves: details
+                .pokemonmoves
+                .into_iter()
+                .map(|pokemon_move| {
+                    let pokemon_move = pokemon_move.r#move;
+                    Move {
+                        name: pokemon_move
+                            .movenames
+                            .into_iter()
+                            .next()
+                            .map_or(pokemon_move.name, |move_name| move_name.name),
+                        power: pokemon_move.power,
+                        accuracy: pokemon_move.accuracy,
+                        priority: pokemon_move.priority,
+                        move_type: pokemon_move.r#type.name,
+                        damage_class: pokemon_move.movedamageclass.name,
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn get_type_efficacy(&self) -> anyhow::Result<TypeEfficacy> {
+        let query: TypeEfficacyQueryResponse = self
+            .post_query(TYPE_EFFICACY_GRAPHQL_QUERY, serde_json::json!({}))
+            .await?;
+        let type_names = query
+            .r#type
+            .into_iter()
+            .map(|pokemon_type| (pokemon_type.id, pokemon_type.name))
+            .collect::<HashMap<_, _>>();
+        Ok(query
+            .typeefficacy
+            .into_iter()
+            .filter_map(|efficacy| {
+                Some((
+                    (
+                        type_names.get(&efficacy.damage_type_id)?.clone(),
+                        type_names.get(&efficacy.target_type_id)?.clone(),
+                    ),
+                    efficacy.damage_factor,
+                ))
+            })
+            .collect())
+    }
+
+    /// Returns every species with its English name and default sprite, ordered by Pokedex number
+    pub async fn get_species(&self) -> anyhow::Result<Vec<Species>> {
+        let query: PokemonSpeciesQueryResponse<SpeciesQueryResponseFields> = self
+            .post_query(SPECIES_GRAPHQL_QUERY, serde_json::json!({}))
+            .await?;
+        Ok(query
+            .pokemonspecies
+            .into_iter()
+            .filter_map(|species| {
+                Some(Species {
+                    id: species.id,
+                    generation: species.generation_id,
+                    name: species.pokemonspeciesnames.into_iter().next()?.name,
+                    sprite_url: species
+                        .pokemons
+                        .into_iter()
+                        .next()?
+                        .pokemonsprites
+                        .into_iter()
+                        .next()?
+                        .sprites
+                        .unwrap_or_default(),
+                })
+            })
+            .collect())
+    }
+
+    /// Downloads the sprite unless it's cached, in which case no request is made
     pub async fn get_sprite(&self, url: &str) -> anyhow::Result<bytes::Bytes> {
-        let res = self.client.get(url).send().await?;
-        Ok(res.bytes().await?)
+        let url_key = Cache::key(url.as_bytes());
+        if let Some(content_key) = self.sprite_cache.get(&url_key, None) {
+            match self
+                .sprite_cache
+                .get(&String::from_utf8_lossy(&content_key), None)
+            {
+                Some(sprite) => return Ok(bytes::Bytes::from(sprite)),
+                // The sprite was evicted, so the URL mustn't point to it anymore
+                None => self.sprite_cache.remove(&url_key),
+            }
+        }
+
+        // Missing variants like `shiny/female/` are answered with a 404, which mustn't be cached
+        let res = self.client.get(url).send().await?.error_for_status()?;
+        let sprite = res.bytes().await?;
+        let content_key = Cache::key(&sprite);
+        self.sprite_cache.put(&content_key, &sprite);
+        self.sprite_cache.put(&url_key, content_key.as_bytes());
+        Ok(sprite)
     }
 }
 
@@ -97,22 +724,100 @@ struct PokemonQueryResponse<T> {
     pokemon: (T,),
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PokemonSpeciesQueryResponse<T> {
+    pokemonspecies: Vec<T>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+struct PokemonAggregateQueryResponse {
+    pokemon_aggregate: AggregateQueryResponseFields,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+struct AggregateQueryResponseFields {
+    aggregate: CountQueryResponseFields,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+struct CountQueryResponseFields {
+    count: i64,
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
-struct LastPokemonQueryResponseFields {
+struct IdQueryResponseFields {
     id: i64,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 struct PokemonSpecyAndSpritesQueryResponseFields {
+    id: i64,
     pokemonspecy: PokemonSpecyQueryResponseFields,
+    // Custom queries which don't ask for the types get Pokemon without types
+    #[serde(default)]
+    pokemontypes: Vec<PokemonTypesQueryResponseFields>,
     pokemonsprites: (PokemonSpritesQueryResponseFields,),
 }
 
+impl From<PokemonSpecyAndSpritesQueryResponseFields> for Pokemon {
+    fn from(pokemon: PokemonSpecyAndSpritesQueryResponseFields) -> Self {
+        Self {
+            id: pokemon.id,
+            species_id: pokemon.pokemonspecy.id,
+            name: pokemon.pokemonspecy.pokemonspeciesnames.0.name,
+            sprite_url: pokemon.pokemonsprites.0.sprites,
+            types: pokemon
+                .pokemontypes
+                .into_iter()
+                .map(|pokemon_type| pokemon_type.r#type.name)
+                .collect(),
+            shiny: false,
+            female: false,
+            gender_rate: pokemon.pokemonspecy.gender_rate,
+            has_gender_differences: pokemon.pokemonspecy.has_gender_differences,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 struct PokemonSpecyQueryResponseFields {
+    id: i64,
+    // Custom queries which don't ask for the gender get genderless Pokemon
+    #[serde(default = "genderless_rate")]
+    gender_rate: i64,
+    #[serde(default)]
+    has_gender_differences: bool,
     pokemonspeciesnames: (PokemonSpeciesNamesQueryResponseFields,),
 }
 
+fn genderless_rate() -> i64 {
+    -1
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PokemonDetailsQueryResponseFields {
+    base_experience: Option<i64>,
+    pokemonspecy: PokemonSpecyDetailsQueryResponseFields,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PokemonTypesQueryResponseFields {
+    r#type: TypeQueryResponseFields,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct TypeQueryResponseFields {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PokemonSpecyDetailsQueryResponseFields {
+    capture_rate: i64,
+    generation_id: i64,
+    growth_rate_id: i64,
+    pokemonspeciesnames: Vec<PokemonSpeciesNamesQueryResponseFields>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 struct PokemonSpeciesNamesQueryResponseFields {
     name: String,
@@ -122,3 +827,159 @@ struct PokemonSpeciesNamesQueryResponseFields {
 struct PokemonSpritesQueryResponseFields {
     sprites: String,
 }
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct SpeciesQueryResponseFields {
+    id: i64,
+    generation_id: i64,
+    pokemonspeciesnames: Vec<PokemonSpeciesNamesQueryResponseFields>,
+    pokemons: Vec<SpeciesPokemonQueryResponseFields>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct SpeciesPokemonQueryResponseFields {
+    pokemonsprites: Vec<SpeciesSpritesQueryResponseFields>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct SpeciesSpritesQueryResponseFields {
+    sprites: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct EvolvedSpeciesQueryResponseFields {
+    id: i64,
+    pokemonevolutions: Vec<PokemonEvolutionsQueryResponseFields>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct EvolutionChainSpeciesQueryResponseFields {
+    id: i64,
+    generation_id: i64,
+    evolves_from_species_id: Option<i64>,
+    pokemonspeciesnames: Vec<PokemonSpeciesNamesQueryResponseFields>,
+    pokemons: Vec<SpeciesPokemonQueryResponseFields>,
+    pokemonevolutions: Vec<PokemonEvolutionsQueryResponseFields>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PokemonEvolutionsQueryResponseFields {
+    min_level: Option<i64>,
+    min_happiness: Option<i64>,
+    item: Option<ItemQueryResponseFields>,
+    evolutiontrigger: EvolutionTriggerQueryResponseFields,
+}
+
+impl PokemonEvolutionsQueryResponseFields {
+    fn into_evolution(self, species_id: i64) -> Evolution {
+        Evolution {
+            species_id,
+            trigger: self.evolutiontrigger.name,
+            min_level: self.min_level,
+            min_happiness: self.min_happiness,
+            item: self.item.map(|item| item.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct ItemQueryResponseFields {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct EvolutionTriggerQueryResponseFields {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct CardDetailsQueryResponseFields {
+    height: Option<i64>,
+    weight: Option<i64>,
+    pokemonspecy: PokemonSpecyGenusQueryResponseFields,
+    pokemonabilities: Vec<PokemonAbilitiesQueryResponseFields>,
+    pokemonstats: Vec<PokemonStatsQueryResponseFields>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PokemonSpecyGenusQueryResponseFields {
+    pokemonspeciesnames: Vec<PokemonSpeciesGenusQueryResponseFields>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PokemonSpeciesGenusQueryResponseFields {
+    genus: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PokemonAbilitiesQueryResponseFields {
+    is_hidden: bool,
+    ability: AbilityQueryResponseFields,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct AbilityQueryResponseFields {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct BattleDetailsQueryResponseFields {
+    pokemonstats: Vec<PokemonStatsQueryResponseFields>,
+    pokemonmoves: Vec<PokemonMovesQueryResponseFields>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PokemonStatsQueryResponseFields {
+    base_stat: i64,
+    stat: StatQueryResponseFields,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct StatQueryResponseFields {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PokemonMovesQueryResponseFields {
+    r#move: MoveQueryResponseFields,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct MoveQueryResponseFields {
+    name: String,
+    power: i64,
+    accuracy: Option<i64>,
+    priority: i64,
+    r#type: TypeQueryResponseFields,
+    movedamageclass: MoveDamageClassQueryResponseFields,
+    movenames: Vec<MoveNamesQueryResponseFields>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct MoveDamageClassQueryResponseFields {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct MoveNamesQueryResponseFields {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct TypeEfficacyQueryResponse {
+    typeefficacy: Vec<TypeEfficacyQueryResponseFields>,
+    r#type: Vec<TypeIdQueryResponseFields>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+struct TypeEfficacyQueryResponseFields {
+    damage_factor: i64,
+    damage_type_id: i64,
+    target_type_id: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct TypeIdQueryResponseFields {
+    id: i64,
+    name: String,
+}
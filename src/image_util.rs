@@ -1,35 +1,436 @@
-use image::{DynamicImage, GenericImageView};
+use image::{
+    DynamicImage, GenericImageView, Rgb, RgbaImage,
+    imageops::{self, FilterType},
+};
 
-pub fn crop_transparent_pixels(img: &DynamicImage) -> DynamicImage {
-    let (width, height) = img.dimensions();
+use crate::{CropMode, Effect};
+
+/// Crops the image to the pixels with an alpha above `alpha_threshold`, plus `padding` pixels on
+/// every side. Returns the image unchanged if every pixel is transparent.
+pub fn crop_transparent_pixels(
+    img: &DynamicImage,
+    alpha_threshold: u8,
+    padding: u32,
+    mode: CropMode,
+) -> DynamicImage {
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let row_len = width as usize * 4;
+    let is_content = |pixel: &[u8]| pixel[3] > alpha_threshold;
+    let rows = rgba.as_raw().chunks_exact(row_len.max(1));
+
+    // Find the vertical bounds first, so the horizontal search can skip the empty rows
+    let Some(min_y) = rows
+        .clone()
+        .position(|row| row.chunks_exact(4).any(is_content))
+    else {
+        return img.clone();
+    };
+    let max_y = height as usize
+        - 1
+        - rows
+            .clone()
+            .rev()
+            .position(|row| row.chunks_exact(4).any(is_content))
+            .unwrap_or(0);
 
-    let mut min_x = width;
-    let mut min_y = height;
+    let mut min_x = width as usize;
     let mut max_x = 0;
-    let mut max_y = 0;
-
-    // Find the bounding box
-    for y in 0..height {
-        for x in 0..width {
-            let pixel = img.get_pixel(x, y);
-            // Check if the alpha channel is not fully transparent (e.g., > 0)
-            if pixel[3] > 0 {
-                min_x = min_x.min(x);
-                min_y = min_y.min(y);
-                max_x = max_x.max(x);
-                max_y = max_y.max(y);
+    for row in rows.skip(min_y).take(max_y - min_y + 1) {
+        let pixels = row.chunks_exact(4);
+        if let Some(x) = pixels.clone().take(min_x).position(is_content) {
+            min_x = x;
+        }
+        if let Some(x) = pixels
+            .rev()
+            .take(width as usize - max_x)
+            .position(is_content)
+        {
+            max_x = width as usize - 1 - x;
+        }
+    }
+
+    let padding = i64::from(padding);
+    let (mut x, mut y) = (min_x as i64 - padding, min_y as i64 - padding);
+    let (mut crop_width, mut crop_height) = (
+        (max_x - min_x + 1) as i64 + 2 * padding,
+        (max_y - min_y + 1) as i64 + 2 * padding,
+    );
+
+    // Grow the crop around its center to get the desired aspect ratio
+    let (target_width, target_height) = match mode {
+        CropMode::Bbox => (crop_width as u64, crop_height as u64),
+        CropMode::Square => {
+            let side = crop_width.max(crop_height) as u64;
+            (side, side)
+        }
+        CropMode::KeepAspect => {
+            let (width, height) = (u64::from(width), u64::from(height));
+            let (crop_width, crop_height) = (crop_width as u64, crop_height as u64);
+            if crop_width * height >= crop_height * width {
+                (crop_width, (crop_width * height).div_ceil(width))
+            } else {
+                ((crop_height * width).div_ceil(height), crop_height)
+            }
+        }
+    };
+    let (target_width, target_height) = (target_width as i64, target_height as i64);
+    x -= (target_width - crop_width) / 2;
+    y -= (target_height - crop_height) / 2;
+    (crop_width, crop_height) = (target_width, target_height);
+
+    if x >= 0
+        && y >= 0
+        && x + crop_width <= i64::from(width)
+        && y + crop_height <= i64::from(height)
+    {
+        return img.crop_imm(x as u32, y as u32, crop_width as u32, crop_height as u32);
+    }
+
+    // The crop extends past the image's edges, so place the image on a larger canvas
+    let mut canvas = RgbaImage::new(crop_width as u32, crop_height as u32);
+    imageops::overlay(&mut canvas, &rgba, -x, -y);
+    DynamicImage::ImageRgba8(canvas)
+}
+
+pub fn scale(img: &DynamicImage, factor: f32, filter: FilterType) -> DynamicImage {
+    let (width, height) = img.dimensions();
+    let scaled_width = ((width as f32 * factor).round() as u32).max(1);
+    let scaled_height = ((height as f32 * factor).round() as u32).max(1);
+    if (scaled_width, scaled_height) == (width, height) {
+        return img.clone();
+    }
+    img.resize_exact(scaled_width, scaled_height, filter)
+}
+
+/// Pads the image with transparent pixels so that it fills a whole number of terminal cells,
+/// returning the padded image with its dimensions in cells
+pub fn pad_to_cells(
+    img: &DynamicImage,
+    (cell_width, cell_height): (u32, u32),
+) -> (DynamicImage, (u32, u32)) {
+    let (width, height) = img.dimensions();
+    let columns = width.div_ceil(cell_width);
+    let rows = height.div_ceil(cell_height);
+
+    // Center horizontally and align to the bottom, so the sprite stands on the text below it
+    let mut padded = RgbaImage::new(columns * cell_width, rows * cell_height);
+    imageops::overlay(
+        &mut padded,
+        &img.to_rgba8(),
+        i64::from((columns * cell_width - width) / 2),
+        i64::from(rows * cell_height - height),
+    );
+
+    (DynamicImage::ImageRgba8(padded), (columns, rows))
+}
+
+/// Blends the image onto an opaque background color, removing all transparency
+pub fn composite_background(img: &DynamicImage, background: Rgb<u8>) -> DynamicImage {
+    let mut composited = img.to_rgba8();
+    for pixel in composited.pixels_mut() {
+        let alpha = u16::from(pixel[3]);
+        for channel in 0..3 {
+            pixel[channel] = ((u16::from(pixel[channel]) * alpha
+                + u16::from(background[channel]) * (255 - alpha))
+                / 255) as u8;
+        }
+        pixel[3] = u8::MAX;
+    }
+    DynamicImage::ImageRgba8(composited)
+}
+
+/// Lays the images out in a grid with `columns` images per row, each centered horizontally and
+/// aligned to the bottom of a cell as large as the largest image
+pub fn tile(images: &[DynamicImage], columns: u32) -> DynamicImage {
+    let columns = columns.max(1);
+    let rows = (images.len() as u32).div_ceil(columns);
+    let cell_width = images.iter().map(|img| img.width()).max().unwrap_or(0);
+    let cell_height = images.iter().map(|img| img.height()).max().unwrap_or(0);
+
+    let mut tiled = RgbaImage::new(
+        columns.min(images.len() as u32) * cell_width,
+        rows * cell_height,
+    );
+    for (i, img) in images.iter().enumerate() {
+        let (column, row) = (i as u32 % columns, i as u32 / columns);
+        imageops::overlay(
+            &mut tiled,
+            &img.to_rgba8(),
+            i64::from(column * cell_width + (cell_width - img.width()) / 2),
+            i64::from(row * cell_height + cell_height - img.height()),
+        );
+    }
+    DynamicImage::ImageRgba8(tiled)
+}
+
+/// Places the images like a battle in the games, `back` in the bottom left corner facing `front`
+/// in the top right corner, overlapping vertically by half of `back`'s height
+pub fn face_off(back: &DynamicImage, front: &DynamicImage) -> DynamicImage {
+    let width = back.width() + front.width();
+    let height = (front.height() + back.height() / 2).max(back.height());
+
+    let mut scene = RgbaImage::new(width, height);
+    imageops::overlay(&mut scene, &front.to_rgba8(), i64::from(back.width()), 0);
+    imageops::overlay(
+        &mut scene,
+        &back.to_rgba8(),
+        0,
+        i64::from(height - back.height()),
+    );
+    DynamicImage::ImageRgba8(scene)
+}
+
+// Visible on both light and dark terminal backgrounds
+const ARROW_COLOR: [u8; 4] = [128, 128, 128, 255];
+
+/// Draws an arrow pointing right across the middle half of a transparent image
+pub fn arrow(width: u32, height: u32) -> DynamicImage {
+    let (start, end) = (width / 4, width - width / 4);
+    let head_length = (end - start) / 3;
+    let shaft_half_height = (height / 48).max(1);
+    let center = height / 2;
+
+    let mut canvas = RgbaImage::new(width, height);
+    for x in start..end {
+        // The head narrows at 45 degrees towards the tip
+        let half_height = if end - x <= head_length {
+            (end - x).max(shaft_half_height)
+        } else {
+            shaft_half_height
+        };
+        for y in center.saturating_sub(half_height)..(center + half_height).min(height) {
+            canvas.put_pixel(x, y, image::Rgba(ARROW_COLOR));
+        }
+    }
+    DynamicImage::ImageRgba8(canvas)
+}
+
+// Shades of the original Game Boy's screen, from darkest to lightest
+const GAMEBOY_PALETTE: [[u8; 3]; 4] = [[15, 56, 15], [48, 98, 48], [139, 172, 15], [155, 188, 15]];
+
+const OUTLINE_COLOR: [u8; 4] = [255, 255, 255, 255];
+const SHADOW_COLOR: [u8; 4] = [0, 0, 0, 96];
+const SHADOW_OFFSET: u32 = 2;
+
+// Pixels with an alpha at or above this are considered part of the sprite by effects
+const EFFECT_ALPHA_THRESHOLD: u8 = 128;
+
+pub fn apply_effect(img: &DynamicImage, effect: Effect) -> DynamicImage {
+    let mut rgba = img.to_rgba8();
+    match effect {
+        Effect::Silhouette => map_colors(&mut rgba, |_| [0, 0, 0]),
+        Effect::Grayscale => map_colors(&mut rgba, |[r, g, b]| [luma(r, g, b); 3]),
+        Effect::Gameboy => map_colors(&mut rgba, |[r, g, b]| {
+            GAMEBOY_PALETTE[usize::from(luma(r, g, b)) * GAMEBOY_PALETTE.len() / 256]
+        }),
+        Effect::Sepia => map_colors(&mut rgba, |[r, g, b]| {
+            let [r, g, b] = [r, g, b].map(f32::from);
+            [
+                0.393 * r + 0.769 * g + 0.189 * b,
+                0.349 * r + 0.686 * g + 0.168 * b,
+                0.272 * r + 0.534 * g + 0.131 * b,
+            ]
+            .map(|channel| channel.min(255.0) as u8)
+        }),
+        Effect::FlipH => imageops::flip_horizontal_in_place(&mut rgba),
+        Effect::Outline => {
+            // Grow the canvas so the outline fits around sprites touching the edges
+            let (width, height) = rgba.dimensions();
+            let mut outlined = RgbaImage::new(width + 2, height + 2);
+            for (x, y, pixel) in outlined.enumerate_pixels_mut() {
+                let is_opaque = |x: u32, y: u32| {
+                    (1..=width).contains(&x)
+                        && (1..=height).contains(&y)
+                        && rgba.get_pixel(x - 1, y - 1)[3] >= EFFECT_ALPHA_THRESHOLD
+                };
+                if !is_opaque(x, y)
+                    && [(0, 1), (2, 1), (1, 0), (1, 2)].iter().any(|&(dx, dy)| {
+                        is_opaque((x + dx).wrapping_sub(1), (y + dy).wrapping_sub(1))
+                    })
+                {
+                    *pixel = image::Rgba(OUTLINE_COLOR);
+                }
+            }
+            imageops::overlay(&mut outlined, &rgba, 1, 1);
+            rgba = outlined;
+        }
+        Effect::Shadow => {
+            let (width, height) = rgba.dimensions();
+            let mut shadowed = RgbaImage::new(width + SHADOW_OFFSET, height + SHADOW_OFFSET);
+            for (x, y, pixel) in rgba.enumerate_pixels() {
+                if pixel[3] >= EFFECT_ALPHA_THRESHOLD {
+                    shadowed.put_pixel(
+                        x + SHADOW_OFFSET,
+                        y + SHADOW_OFFSET,
+                        image::Rgba(SHADOW_COLOR),
+                    );
+                }
             }
+            imageops::overlay(&mut shadowed, &rgba, 0, 0);
+            rgba = shadowed;
+        }
+    }
+    DynamicImage::ImageRgba8(rgba)
+}
+
+fn map_colors(img: &mut RgbaImage, f: impl Fn([u8; 3]) -> [u8; 3]) {
+    for pixel in img.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        let [r, g, b] = f([r, g, b]);
+        pixel.0 = [r, g, b, a];
+    }
+}
+
+fn luma(r: u8, g: u8, b: u8) -> u8 {
+    ((299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)) / 1000) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    /// Transparent image with the given pixels set to white with their alpha
+    fn image(width: u32, height: u32, pixels: &[(u32, u32, u8)]) -> DynamicImage {
+        let mut img = RgbaImage::new(width, height);
+        for &(x, y, alpha) in pixels {
+            img.put_pixel(x, y, Rgba([255, 255, 255, alpha]));
+        }
+        DynamicImage::ImageRgba8(img)
+    }
+
+    fn alpha(img: &DynamicImage, x: u32, y: u32) -> u8 {
+        img.get_pixel(x, y)[3]
+    }
+
+    #[test]
+    fn crop_keeps_the_pixels_above_the_alpha_threshold() {
+        let img = image(10, 10, &[(3, 4, 255), (7, 6, 50)]);
+
+        let cropped = crop_transparent_pixels(&img, 0, 0, CropMode::Bbox);
+        assert_eq!(cropped.dimensions(), (5, 3));
+        assert_eq!(alpha(&cropped, 0, 0), 255);
+        assert_eq!(alpha(&cropped, 4, 2), 50);
+
+        let cropped = crop_transparent_pixels(&img, 100, 0, CropMode::Bbox);
+        assert_eq!(cropped.dimensions(), (1, 1));
+        assert_eq!(alpha(&cropped, 0, 0), 255);
+    }
+
+    #[test]
+    fn crop_pads_and_grows_the_canvas_past_the_edges() {
+        let img = image(10, 10, &[(3, 4, 255)]);
+        let cropped = crop_transparent_pixels(&img, 0, 2, CropMode::Bbox);
+        assert_eq!(cropped.dimensions(), (5, 5));
+        assert_eq!(alpha(&cropped, 2, 2), 255);
+
+        let img = image(4, 4, &[(0, 0, 255)]);
+        let cropped = crop_transparent_pixels(&img, 0, 2, CropMode::Bbox);
+        assert_eq!(cropped.dimensions(), (5, 5));
+        assert_eq!(alpha(&cropped, 2, 2), 255);
+        assert_eq!(alpha(&cropped, 0, 0), 0);
+    }
+
+    #[test]
+    fn crop_grows_around_the_center_to_the_aspect_ratio() {
+        let img = image(10, 10, &[(3, 4, 255), (7, 6, 255)]);
+        let cropped = crop_transparent_pixels(&img, 0, 0, CropMode::Square);
+        assert_eq!(cropped.dimensions(), (5, 5));
+        assert_eq!(alpha(&cropped, 0, 1), 255);
+        assert_eq!(alpha(&cropped, 4, 3), 255);
+
+        // A 2x2 sprite in a 2:1 image keeps the image's aspect ratio
+        let img = image(10, 5, &[(4, 2, 255), (5, 3, 255)]);
+        let cropped = crop_transparent_pixels(&img, 0, 0, CropMode::KeepAspect);
+        assert_eq!(cropped.dimensions(), (4, 2));
+        assert_eq!(alpha(&cropped, 1, 0), 255);
+        assert_eq!(alpha(&cropped, 2, 1), 255);
+    }
+
+    #[test]
+    fn crop_leaves_fully_transparent_images_unchanged() {
+        let img = image(6, 4, &[(1, 1, 10)]);
+        for mode in [CropMode::Bbox, CropMode::Square, CropMode::KeepAspect] {
+            assert_eq!(crop_transparent_pixels(&img, 10, 1, mode), img);
+        }
+    }
+
+    fn rgba(img: &DynamicImage, x: u32, y: u32) -> [u8; 4] {
+        img.get_pixel(x, y).0
+    }
+
+    /// 2x1 image with an orange pixel on the left and a transparent one on the right
+    fn orange() -> DynamicImage {
+        let mut img = RgbaImage::new(2, 1);
+        img.put_pixel(0, 0, Rgba([240, 128, 48, 255]));
+        DynamicImage::ImageRgba8(img)
+    }
+
+    #[test]
+    fn color_effects_keep_the_alpha() {
+        let img = orange();
+        let apply = |effect| rgba(&apply_effect(&img, effect), 0, 0);
+
+        assert_eq!(apply(Effect::Silhouette), [0, 0, 0, 255]);
+        let luma = luma(240, 128, 48);
+        assert_eq!(apply(Effect::Grayscale), [luma, luma, luma, 255]);
+        let [r, g, b] = GAMEBOY_PALETTE[usize::from(luma) * GAMEBOY_PALETTE.len() / 256];
+        assert_eq!(apply(Effect::Gameboy), [r, g, b, 255]);
+        assert_eq!(apply(Effect::Sepia), [201, 179, 139, 255]);
+        for effect in [
+            Effect::Silhouette,
+            Effect::Grayscale,
+            Effect::Gameboy,
+            Effect::Sepia,
+        ] {
+            assert_eq!(rgba(&apply_effect(&img, effect), 1, 0)[3], 0);
         }
     }
 
-    // If no non-transparent pixels are found, return an empty image or handle as appropriate
-    if min_x > max_x || min_y > max_y {
-        return DynamicImage::new_rgba8(0, 0); // Or handle empty image case
+    #[test]
+    fn gameboy_maps_black_and_white_to_the_ends_of_the_palette() {
+        let img = image(2, 1, &[(0, 0, 255)]);
+        let gameboy = apply_effect(&img, Effect::Gameboy);
+        assert_eq!(rgba(&gameboy, 0, 0)[..3], GAMEBOY_PALETTE[3]);
+        assert_eq!(rgba(&gameboy, 1, 0)[..3], GAMEBOY_PALETTE[0]);
+    }
+
+    #[test]
+    fn flip_h_mirrors_the_pixels() {
+        let flipped = apply_effect(&orange(), Effect::FlipH);
+        assert_eq!(rgba(&flipped, 0, 0)[3], 0);
+        assert_eq!(rgba(&flipped, 1, 0), [240, 128, 48, 255]);
     }
 
-    // Calculate the new dimensions and crop
-    let crop_width = max_x - min_x + 1;
-    let crop_height = max_y - min_y + 1;
+    #[test]
+    fn outline_surrounds_the_opaque_pixels() {
+        let mut img = RgbaImage::new(3, 3);
+        img.put_pixel(1, 1, Rgba([240, 128, 48, 255]));
+        // Too transparent to be outlined
+        img.put_pixel(0, 0, Rgba([240, 128, 48, EFFECT_ALPHA_THRESHOLD - 1]));
+        let outlined = apply_effect(&DynamicImage::ImageRgba8(img), Effect::Outline);
 
-    img.crop_imm(min_x, min_y, crop_width, crop_height)
+        assert_eq!(outlined.dimensions(), (5, 5));
+        assert_eq!(rgba(&outlined, 2, 2), [240, 128, 48, 255]);
+        for (x, y) in [(1, 2), (3, 2), (2, 1), (2, 3)] {
+            assert_eq!(rgba(&outlined, x, y), OUTLINE_COLOR);
+        }
+        assert_eq!(rgba(&outlined, 3, 3)[3], 0);
+        assert_eq!(rgba(&outlined, 0, 1)[3], 0);
+    }
+
+    #[test]
+    fn shadow_is_offset_behind_the_opaque_pixels() {
+        let shadowed = apply_effect(&orange(), Effect::Shadow);
+
+        assert_eq!(
+            shadowed.dimensions(),
+            (2 + SHADOW_OFFSET, 1 + SHADOW_OFFSET)
+        );
+        assert_eq!(rgba(&shadowed, 0, 0), [240, 128, 48, 255]);
+        assert_eq!(rgba(&shadowed, SHADOW_OFFSET, SHADOW_OFFSET), SHADOW_COLOR);
+        assert_eq!(rgba(&shadowed, 1 + SHADOW_OFFSET, SHADOW_OFFSET)[3], 0);
+    }
 }
@@ -0,0 +1,19 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "box_pokemon")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub pokemon_id: i64,
+    pub species_id: i64,
+    pub name: String,
+    pub shiny: bool,
+    pub ball: String,
+    pub caught_at: ChronoDateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,47 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Encounter::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Encounter::Id))
+                    .col(big_integer(Encounter::SpeciesId))
+                    .col(boolean(Encounter::Shiny))
+                    .col(timestamp_with_time_zone(Encounter::EncounteredAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-encounter-species_id")
+                    .table(Encounter::Table)
+                    .col(Encounter::SpeciesId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Encounter::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Encounter {
+    Table,
+    Id,
+    SpeciesId,
+    Shiny,
+    EncounteredAt,
+}
@@ -0,0 +1,43 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BoxPokemon::Table)
+                    .if_not_exists()
+                    .col(pk_auto(BoxPokemon::Id))
+                    .col(big_integer(BoxPokemon::PokemonId))
+                    .col(big_integer(BoxPokemon::SpeciesId))
+                    .col(string(BoxPokemon::Name))
+                    .col(boolean(BoxPokemon::Shiny))
+                    .col(string(BoxPokemon::Ball))
+                    .col(timestamp_with_time_zone(BoxPokemon::CaughtAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BoxPokemon::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BoxPokemon {
+    Table,
+    Id,
+    PokemonId,
+    SpeciesId,
+    Name,
+    Shiny,
+    Ball,
+    CaughtAt,
+}
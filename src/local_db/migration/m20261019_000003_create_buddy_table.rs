@@ -0,0 +1,37 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Buddy::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Buddy::Id))
+                    .col(big_integer(Buddy::SpeciesId))
+                    .col(big_integer(Buddy::Experience))
+                    .col(timestamp_with_time_zone(Buddy::ChosenAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Buddy::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Buddy {
+    Table,
+    Id,
+    SpeciesId,
+    Experience,
+    ChosenAt,
+}
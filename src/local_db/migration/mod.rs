@@ -0,0 +1,18 @@
+use sea_orm_migration::prelude::*;
+
+mod m20261019_000001_create_encounter_table;
+mod m20261019_000002_create_box_pokemon_table;
+mod m20261019_000003_create_buddy_table;
+
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(m20261019_000001_create_encounter_table::Migration),
+            Box::new(m20261019_000002_create_box_pokemon_table::Migration),
+            Box::new(m20261019_000003_create_buddy_table::Migration),
+        ]
+    }
+}
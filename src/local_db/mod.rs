@@ -0,0 +1,158 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use sea_orm::{ActiveValue::Set, ConnectOptions, Database, QueryOrder, QuerySelect, prelude::*};
+use sea_orm_migration::MigratorTrait;
+
+use crate::{Pokemon, xdg_dir};
+
+pub mod box_pokemon;
+pub mod buddy;
+mod encounter;
+mod migration;
+
+const DB_FILE_NAME: &str = "pokemonsay.sqlite3";
+
+/// Database for the state kept between runs, stored in the data directory
+pub struct LocalDb {
+    db: DatabaseConnection,
+}
+
+impl LocalDb {
+    pub async fn new(data_dir: &Option<impl AsRef<Path>>) -> anyhow::Result<Self> {
+        let data_dir = match data_dir {
+            Some(data_dir) => data_dir.as_ref().to_path_buf(),
+            None => default_data_dir()?,
+        };
+        fs::create_dir_all(&data_dir)?;
+
+        let db = Database::connect(ConnectOptions::new(format!(
+            "sqlite://{}?mode=rwc",
+            data_dir.join(DB_FILE_NAME).to_string_lossy()
+        )))
+        .await?;
+        migration::Migrator::up(&db, None).await?;
+
+        Ok(Self { db })
+    }
+
+    /// Records an encounter with a Pokemon, returning whether its species was encountered for
+    /// the first time
+    pub async fn record_encounter(&self, species_id: i64, shiny: bool) -> anyhow::Result<bool> {
+        let new = encounter::Entity::find()
+            .filter(encounter::Column::SpeciesId.eq(species_id))
+            .one(&self.db)
+            .await?
+            .is_none();
+
+        encounter::ActiveModel {
+            species_id: Set(species_id),
+            shiny: Set(shiny),
+            encountered_at: Set(chrono::Utc::now()),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await?;
+
+        Ok(new)
+    }
+
+    /// Returns the ids of the `limit` most recently encountered species
+    pub async fn get_recent_species(&self, limit: u64) -> anyhow::Result<Vec<i64>> {
+        let recent = encounter::Entity::find()
+            .select_only()
+            .column(encounter::Column::SpeciesId)
+            .group_by(encounter::Column::SpeciesId)
+            .order_by_desc(encounter::Column::Id.max())
+            .limit(limit)
+            .into_tuple::<i64>()
+            .all(&self.db)
+            .await?;
+
+        Ok(recent)
+    }
+
+    /// Returns the ids of the species encountered so far, along with whether any of their
+    /// encounters were shiny
+    pub async fn get_seen_species(&self) -> anyhow::Result<HashMap<i64, bool>> {
+        let seen = encounter::Entity::find()
+            .select_only()
+            .column(encounter::Column::SpeciesId)
+            .column_as(encounter::Column::Shiny.max(), "shiny")
+            .group_by(encounter::Column::SpeciesId)
+            .into_tuple::<(i64, bool)>()
+            .all(&self.db)
+            .await?;
+
+        Ok(seen.into_iter().collect())
+    }
+
+    /// Stores a caught Pokemon in the box
+    pub async fn add_to_box(&self, pokemon: &Pokemon, ball: &str) -> anyhow::Result<()> {
+        box_pokemon::ActiveModel {
+            pokemon_id: Set(pokemon.id),
+            species_id: Set(pokemon.species_id),
+            name: Set(pokemon.name.clone()),
+            shiny: Set(pokemon.shiny),
+            ball: Set(ball.to_string()),
+            caught_at: Set(chrono::Utc::now()),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the Pokemon in the box, in the order they were caught
+    pub async fn get_box(&self) -> anyhow::Result<Vec<box_pokemon::Model>> {
+        Ok(box_pokemon::Entity::find()
+            .order_by_asc(box_pokemon::Column::Id)
+            .all(&self.db)
+            .await?)
+    }
+
+    pub async fn get_buddy(&self) -> anyhow::Result<Option<buddy::Model>> {
+        Ok(buddy::Entity::find().one(&self.db).await?)
+    }
+
+    /// Replaces the buddy with a new one
+    pub async fn set_buddy(
+        &self,
+        species_id: i64,
+        experience: i64,
+    ) -> anyhow::Result<buddy::Model> {
+        buddy::Entity::delete_many().exec(&self.db).await?;
+        Ok(buddy::ActiveModel {
+            species_id: Set(species_id),
+            experience: Set(experience),
+            chosen_at: Set(chrono::Utc::now()),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await?)
+    }
+
+    /// Updates the buddy's species and experience after it levels up or evolves
+    pub async fn update_buddy(
+        &self,
+        buddy: buddy::Model,
+        species_id: i64,
+        experience: i64,
+    ) -> anyhow::Result<()> {
+        let mut buddy: buddy::ActiveModel = buddy.into();
+        buddy.species_id = Set(species_id);
+        buddy.experience = Set(experience);
+        buddy.update(&self.db).await?;
+        Ok(())
+    }
+}
+
+fn default_data_dir() -> anyhow::Result<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+        .map(|data_dir| data_dir.join(env!("CARGO_PKG_NAME")))
+        .ok_or_else(|| anyhow::anyhow!("Data directory not found!"))
+}
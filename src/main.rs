@@ -1,36 +1,143 @@
+mod battle;
+mod bubble;
+mod buddy;
+mod cache;
+mod card;
+mod catch;
+mod color;
+mod config;
 mod db;
+mod dex;
+mod evolution;
+mod graphics;
 mod http;
 mod image_util;
+mod local_db;
 #[allow(clippy::all, dead_code)]
 mod pokeapi_db;
+mod quiz;
+mod template;
+mod terminal;
+mod variables;
 
 use std::{
+    collections::{BTreeMap, HashMap},
+    env,
     io::{self, BufRead, IsTerminal},
-    path::PathBuf,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::FromStr,
 };
 
 #[cfg(not(feature = "embed-db"))]
 use clap::builder::ArgPredicate;
-use clap::{Parser, ValueEnum};
-use image::GenericImageView;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use image::{DynamicImage, GenericImageView, ImageFormat, Rgb, imageops::FilterType};
 use serde::Serialize;
 use tinytemplate::TinyTemplate;
 
 use crate::{
+    color::{ColorDepth, type_color},
+    config::Config,
     db::{DEFAULT_POKEMON_QUERY, DEFAULT_SPECIES_NAME_QUERY, DEFAULT_SPRITES_QUERY, Db},
-    http::{DEFAULT_GRAPHQL_QUERY, Http},
+    graphics::{DEFAULT_CELL_SIZE, Graphics, find_best_fit, fit_dimensions, fit_within},
+    http::{DEFAULT_GRAPHQL_QUERY, DEFAULT_SPRITE_CACHE_SIZE, Http},
+    local_db::LocalDb,
+    template::Template,
 };
 
-const DEFAULT_POKEMONSAY_TEMPLATE: &str = "Wild {pokemon} appeared!";
+const DEFAULT_POKEMONSAY_TEMPLATE: &str = "Wild {pokemon | upper | type_color} appeared!";
+
+// Width of a Pokemon's cell when displaying several Pokemon, in terminal columns
+const TEAM_SPRITE_COLUMNS: u32 = 20;
+
+// Assumed when the terminal's width can't be detected
+const DEFAULT_COLUMNS: u32 = 80;
+
+// Below these dimensions, only the message is displayed
+const MIN_SPRITE_COLUMNS: u32 = 8;
+const MIN_SPRITE_ROWS: u32 = 4;
+
+// Chance of encountering a shiny Pokemon since generation VI
+const DEFAULT_SHINY_ODDS: u32 = 4096;
+
+const WILD_LEVELS: RangeInclusive<i64> = 2..=50;
+
+const NATURES: [&str; 25] = [
+    "Hardy", "Lonely", "Brave", "Adamant", "Naughty", "Bold", "Docile", "Relaxed", "Impish", "Lax",
+    "Timid", "Hasty", "Serious", "Jolly", "Naive", "Modest", "Mild", "Quiet", "Bashful", "Rash",
+    "Calm", "Gentle", "Sassy", "Careful", "Quirky",
+];
+
+// Individual values range from 0 to 31 since generation III
+const MAX_IV: i64 = 31;
 
 #[derive(Debug, Clone, Serialize)]
 struct PokemonsayTemplateContext {
+    /// Variables like `{hostname}` and the ones from `--var`, which come first so that the fields
+    /// below take precedence over variables of the same name
+    #[serde(flatten)]
+    variables: BTreeMap<String, String>,
+    /// Names of every Pokemon, e.g. `Pikachu, Eevee and Mew` with `--count 3`
     pokemon: String,
+    /// Pokedex number of the first Pokemon, the same goes for its types
+    number: i64,
+    types: Vec<String>,
+    /// Whether any of the Pokemon is shiny
+    shiny: bool,
+    /// Whether any of the Pokemon was added to the Pokedex by this encounter
+    new: bool,
+    /// Level of the first Pokemon, the same goes for its gender, nature and IVs
+    level: i64,
+    gender: &'static str,
+    nature: &'static str,
+    ivs: Stats,
+    /// Context of every Pokemon on its own
+    team: Vec<WildPokemonContext>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct WildPokemonContext {
+    pokemon: String,
+    number: i64,
+    /// Types ordered by slot, e.g. `grass` and `poison`
+    types: Vec<String>,
+    shiny: bool,
+    /// Whether the Pokemon was added to the Pokedex by this encounter
+    new: bool,
+    level: i64,
+    /// `♂`, `♀` or empty when the Pokemon is genderless
+    gender: &'static str,
+    nature: &'static str,
+    ivs: Stats,
 }
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Opt {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to the configuration file, a TOML file which can set any option by its long name,
+    /// e.g. `shiny-odds = 100`
+    ///
+    /// Can also be set via the `POKEMONSAY_CONFIG` environment variable.
+    ///
+    /// Defaults to `pokemonsay/config.toml` in the XDG config directory, i.e.
+    /// `$XDG_CONFIG_HOME/pokemonsay/config.toml` or `~/.config/pokemonsay/config.toml` on every
+    /// platform, when it exists. Options given on the command line or
+    /// by environment variables take precedence over the configuration file.
+    #[arg(long, env = "POKEMONSAY_CONFIG", global = true)]
+    config: Option<PathBuf>,
+
+    /// Profile of the configuration file to apply on top of its top-level options
+    ///
+    /// Can also be set via the `POKEMONSAY_PROFILE` environment variable.
+    ///
+    /// Profiles are tables under `profiles`, e.g. `[profiles.motd]` for `--profile motd`.
+    #[arg(long, env = "POKEMONSAY_PROFILE", global = true)]
+    profile: Option<String>,
+
     /// Specifies the method to use for fetching Pokemon data
     ///
     /// `db`: Uses the database (embedded in the CLI or provided via `--db-path`).
@@ -56,6 +163,31 @@ struct Opt {
     )]
     query_method: QueryMethod,
 
+    /// Specifies how the responses of PokeAPI's GraphQL API and the sprites downloaded with
+    /// `--sprites-retrieval-method http` are cached, sparing requests
+    ///
+    /// Both are kept in `pokemonsay` in the XDG cache directory, i.e. `$XDG_CACHE_HOME/pokemonsay`
+    /// or `~/.cache/pokemonsay`. Responses are kept for a week, except for the number of Pokemon
+    /// which is refreshed daily, and sprites until `--sprite-cache-size` is reached.
+    ///
+    /// `on`: Uses the cached responses and sprites and caches new ones.
+    ///
+    /// `off`: Neither uses nor caches responses and sprites.
+    ///
+    /// `read-only`: Uses the cached responses and sprites without caching new ones.
+    ///
+    /// `refresh`: Caches new responses and sprites without using the cached ones.
+    ///
+    /// `cache prefetch` needs `on` or `refresh`.
+    #[arg(long, default_value = "on", global = true)]
+    cache: CacheMode,
+
+    /// Size of the cache of sprites downloaded with `--sprites-retrieval-method http`, in MiB
+    ///
+    /// Sprites are kept until the cache is full, evicting the least recently used ones first.
+    #[arg(long, default_value_t = DEFAULT_SPRITE_CACHE_SIZE, global = true)]
+    sprite_cache_size: u64,
+
     /// Path to the PokeAPI SQLite database
     ///
     /// Can also be set via the `POKEMONSAY_DB_PATH` environment variable.
@@ -79,6 +211,9 @@ struct Opt {
     /// `embedded`: Uses sprite images embedded within the CLI.
     ///
     /// `http`: Downloads sprites from `https://raw.githubusercontent.com/PokeAPI/sprites/master`.
+    ///
+    /// `dir`: Reads sprites from a checkout of the PokeAPI sprites repository given by
+    ///        `--sprites-dir`.
     #[cfg(feature = "embed-sprites")]
     #[arg(long, default_value = "embedded")]
     sprites_retrieval_method: SpriteRetrievalMethod,
@@ -86,17 +221,33 @@ struct Opt {
     /// Specifies the method to use for retrieving Pokemon sprite images
     ///
     /// `http`: Downloads sprites from `https://raw.githubusercontent.com/PokeAPI/sprites/master`.
+    ///
+    /// `dir`: Reads sprites from a checkout of the PokeAPI sprites repository given by
+    ///        `--sprites-dir`.
     #[cfg(not(feature = "embed-sprites"))]
     #[arg(long, default_value = "http")]
     sprites_retrieval_method: SpriteRetrievalMethod,
 
-    #[arg(long, default_value = DEFAULT_POKEMON_QUERY, hide_default_value = true, help = format!("Custom SQL query to fetch Pokemon data from the database\n\nOnly used when `--query-method db` is set.\n\nDefault value:\n```sql{}```", DEFAULT_POKEMON_QUERY))]
+    /// Path to a checkout of `https://github.com/PokeAPI/sprites`, containing the `sprites`
+    /// directory
+    ///
+    /// Can also be set via the `POKEMONSAY_SPRITES_DIR` environment variable.
+    ///
+    /// Required if the CLI reads sprites with `--sprites-retrieval-method dir`.
+    #[arg(
+        long,
+        env = "POKEMONSAY_SPRITES_DIR",
+        required_if_eq("sprites_retrieval_method", "dir")
+    )]
+    sprites_dir: Option<PathBuf>,
+
+    #[arg(long, default_value = DEFAULT_POKEMON_QUERY, hide_default_value = true, help = format!("Custom SQL query to fetch Pokemon data from the database\n\nOnly used when `--query-method db` is set. The species ids to avoid with `--no-repeat` are bound to `$1` as a JSON array, and the number of Pokemon to pick with `--count` to `$2`.\n\nDefault value:\n```sql{}```", DEFAULT_POKEMON_QUERY))]
     db_pokemon_query: String,
 
-    #[arg(long, default_value = DEFAULT_SPRITES_QUERY, hide_default_value = true, help = format!("Custom SQL query to fetch Pokemon sprite URLs from the database\n\nOnly used when `--query-method db` is set.\n\nDefault value:\n```sql{}```", DEFAULT_SPRITES_QUERY))]
+    #[arg(long, default_value = DEFAULT_SPRITES_QUERY, hide_default_value = true, help = format!("Custom SQL query to fetch Pokemon sprite URLs from the database\n\nOnly used when `--query-method db` is set and `--db-pokemon-query` doesn't return a `sprite_url` column.\n\nDefault value:\n```sql{}```", DEFAULT_SPRITES_QUERY))]
     db_sprites_query: String,
 
-    #[arg(long, default_value = DEFAULT_SPECIES_NAME_QUERY, hide_default_value = true, help = format!("Custom SQL query to fetch Pokemon species names from the database\n\nOnly used when `--query-method db` is set.\n\nDefault value:\n```sql{}```", DEFAULT_SPECIES_NAME_QUERY))]
+    #[arg(long, default_value = DEFAULT_SPECIES_NAME_QUERY, hide_default_value = true, help = format!("Custom SQL query to fetch Pokemon species names from the database\n\nOnly used when `--query-method db` is set and `--db-pokemon-query` doesn't return a `species_name` column.\n\nDefault value:\n```sql{}```", DEFAULT_SPECIES_NAME_QUERY))]
     db_species_name_query: String,
 
     #[arg(long, default_value = DEFAULT_GRAPHQL_QUERY, hide_default_value = true, help = format!("Custom GraphQL query to fetch Pokemon data\n\nOnly used when `--query-method http` is set.\n\nDefault value:\n```graphql{}```", DEFAULT_GRAPHQL_QUERY))]
@@ -106,25 +257,458 @@ struct Opt {
     ///
     /// Uses TinyTemplate syntax with `{pokemon}` as the Pokemon name placeholder.
     ///
+    /// `{shiny}` and `{new}` are set when the Pokemon is shiny or a new Pokedex entry, e.g.
+    /// `{{ if new }}New Pokedex entry! {{ endif }}Wild {pokemon} appeared!`.
+    ///
+    /// `{level}`, `{gender}` (`♂`, `♀` or empty), `{nature}` and `{ivs.hp}`, `{ivs.attack}`,
+    /// `{ivs.defense}`, `{ivs.special_attack}`, `{ivs.special_defense}`, `{ivs.speed}` are rolled
+    /// for every encounter, e.g. `Wild {pokemon} {gender} Lv.{level} appeared!`.
+    ///
+    /// With `--count`, `{pokemon}` lists every Pokemon and `{team}` holds the fields of each one,
+    /// e.g. `{{ for member in team }}{member.pokemon} Lv.{member.level} {{ endfor }}`.
+    ///
+    /// `{pokemon}` is the Pokemon's name as is, `{number}` its Pokedex number and `{types}` its
+    /// types, e.g. `{{ for type in types }}{type | title} {{ endfor }}`.
+    ///
+    /// Values can be transformed by chaining filters, e.g. `{pokemon | upper | type_color}` or
+    /// `No. {number | number:03}`: `upper`, `lower`, `title`, `pad:WIDTH` (pads with spaces on the
+    /// right), `number:WIDTH` (pads with zeros when `WIDTH` starts with `0`) and `type_color`
+    /// (colors the Pokemon names by their primary type when colors are enabled).
+    ///
     /// Can be overridden by piping text to stdin.
     #[arg(long, default_value = DEFAULT_POKEMONSAY_TEMPLATE)]
     pokemonsay_template: String,
 
+    /// File to read the message template from, instead of `--pokemonsay-template`
+    ///
+    /// The file can start with front matter like the files of `--template-dir`.
+    #[arg(long, conflicts_with = "template_dir")]
+    template_file: Option<PathBuf>,
+
+    /// Directory of template files to pick the message template from at random on every run
+    ///
+    /// Every template is equally likely unless its front matter sets its `weight`, e.g. a template
+    /// starting with `---`, `weight: 3` and `---` on their own lines is picked three times as often
+    /// as one without. Hidden files are skipped.
+    #[arg(long)]
+    template_dir: Option<PathBuf>,
+
+    /// Variable passed to the message template, e.g. `--var greeting=Hello` for `{greeting}`
+    ///
+    /// Can be repeated. Environment variables starting with `POKEMONSAY_VAR_` are passed too, e.g.
+    /// `POKEMONSAY_VAR_GREETING` as `{greeting}`, but `--var` takes precedence.
+    ///
+    /// `{hostname}`, `{user}`, `{date}`, `{time}` and `{branch}` (the git branch of the current
+    /// directory, empty outside of a repository) are always available and can be overridden.
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = variables::parse_var)]
+    vars: Vec<(String, String)>,
+
+    /// Whether to color the speech bubble's border by the Pokemon's primary type
+    #[arg(long)]
+    type_colored_bubble: bool,
+
+    /// Number of distinct Pokemon to display side by side
+    ///
+    /// Wraps to more rows when the terminal is too narrow, with the names of the Pokemon
+    /// underneath and a single message below them all.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    count: u32,
+
+    /// Whether to display the Pokemon's whole evolution chain instead of its sprite alone
+    ///
+    /// Shows every species of the chain joined by arrows labeled with how to evolve, with the
+    /// encountered Pokemon outlined. Branching chains like Eevee's take a row per branch.
+    #[arg(long, conflicts_with = "count")]
+    show_evolutions: bool,
+
+    /// Whether to display a Pokedex entry beside the Pokemon sprite
+    ///
+    /// Shows the Pokemon's number, name, genus, types, height, weight, abilities and base stats,
+    /// moving below the sprite when the terminal is too narrow.
+    #[arg(long, conflicts_with_all = ["count", "show_evolutions"])]
+    card: bool,
+
+    /// PNG file to save the Pokemon sprites to instead of displaying them
+    ///
+    /// The sprites are laid out like in the terminal, without the names underneath. Evolution
+    /// chains and cards can't be exported, as their text would need a font bundled into the
+    /// binary.
+    #[arg(long, conflicts_with_all = ["show_evolutions", "card"])]
+    export: Option<PathBuf>,
+
+    /// Directory where the Pokedex of encountered Pokemon is stored
+    ///
+    /// Can also be set via the `POKEMONSAY_DATA_DIR` environment variable.
+    ///
+    /// Defaults to `pokemonsay` in the XDG data directory, i.e. `$XDG_DATA_HOME/pokemonsay` or
+    /// `~/.local/share/pokemonsay` on every platform.
+    #[arg(long, env = "POKEMONSAY_DATA_DIR", global = true)]
+    data_dir: Option<PathBuf>,
+
+    /// Whether to skip recording the encountered Pokemon in the Pokedex
+    #[arg(long)]
+    no_pokedex: bool,
+
+    /// Number of recently encountered Pokemon to avoid encountering again
+    ///
+    /// Uses the encounters recorded in the Pokedex. With `--no-pokedex`, the encounters recorded
+    /// before are still avoided, but new ones aren't added.
+    #[arg(long, default_value_t = 0)]
+    no_repeat: u64,
+
+    /// Odds of encountering a shiny Pokemon, as 1 in N
+    #[arg(long, default_value_t = DEFAULT_SHINY_ODDS, value_parser = clap::value_parser!(u32).range(1..))]
+    shiny_odds: u32,
+
     /// Whether to crop transparent pixels from the Pokemon sprite background
     ///
     /// When enabled, removes transparent padding around the sprite image for a tighter display.
     #[arg(long)]
     crop_sprite_transparent_bg: bool,
 
+    /// Alpha value at or below which pixels are considered transparent when cropping
+    ///
+    /// Raising this ignores faint shadows and anti-aliasing around the sprite.
+    #[arg(long, default_value_t = 0, requires = "crop_sprite_transparent_bg")]
+    crop_alpha_threshold: u8,
+
+    /// Number of transparent pixels to keep around the sprite when cropping
+    #[arg(long, default_value_t = 0, requires = "crop_sprite_transparent_bg")]
+    crop_padding: u32,
+
+    /// Specifies the shape of the cropped sprite
+    ///
+    /// `bbox`: Crops to the bounding box of the sprite.
+    ///
+    /// `square`: Grows the bounding box into a square around the sprite.
+    ///
+    /// `keep-aspect`: Grows the bounding box to the aspect ratio of the original sprite image.
+    #[arg(long, default_value = "bbox", requires = "crop_sprite_transparent_bg")]
+    crop_mode: CropMode,
+
+    /// Effects applied to the Pokemon sprite, in the given order
+    ///
+    /// `silhouette`: Blacks out the sprite.
+    ///
+    /// `grayscale`: Removes the sprite's colors.
+    ///
+    /// `gameboy`: Quantizes the sprite to the 4 green shades of the original Game Boy.
+    ///
+    /// `sepia`: Tints the sprite like an old photograph.
+    ///
+    /// `flip-h`: Mirrors the sprite horizontally.
+    ///
+    /// `outline`: Draws a white 1px border around the sprite.
+    ///
+    /// `shadow`: Draws a drop shadow below and to the right of the sprite.
+    ///
+    /// Can be given multiple times or as a comma separated list, e.g. `--effect gameboy,outline`.
+    #[arg(long = "effect", value_delimiter = ',')]
+    effects: Vec<Effect>,
+
     /// Maximum dimension (width or height) for displaying the Pokemon sprite in the terminal
     ///
     /// A value of `0` will disable setting a max dimension.
     ///
-    /// For terminals that don't support graphics protocols, defaults to `0`.
+    /// Defaults to `0` with the Blocks protocol, which terminals without an image protocol fall
+    /// back to, and to `30` with the Kitty, iTerm2, Sixel and ASCII protocols.
+    #[arg(long)]
+    max_sprite_dimension: Option<u32>,
+
+    /// Specifies the graphics protocol used to display the Pokemon sprite
+    ///
+    /// `auto`: Detects the protocol supported by the terminal, looking through tmux and screen.
+    ///         Uses `ascii` when colors are disabled.
+    ///
+    /// `kitty`: Uses the Kitty graphics protocol.
+    ///
+    /// `iterm`: Uses the iTerm2 inline images protocol.
+    ///
+    /// `sixel`: Uses Sixel graphics.
+    ///
+    /// `blocks`: Uses colored unicode half blocks, supported by most terminals.
+    ///
+    /// `ascii`: Draws the sprite with plain ASCII characters, without any escape sequences.
+    ///
+    /// `text`: Doesn't draw the sprite, only displaying the Pokemon's name above the message.
+    ///
+    /// Inside tmux or screen, escape sequences are wrapped to be passed through to the outer
+    /// terminal. For tmux this requires `set -g allow-passthrough on`.
+    #[arg(long, default_value = "auto")]
+    protocol: GraphicsProtocol,
+
+    /// Whether to display Kitty graphics using unicode placeholders
+    ///
+    /// Placeholders are text, so the sprite scrolls and gets cleared along with the rest of the
+    /// terminal's contents.
+    ///
+    /// Always enabled inside tmux or screen.
+    #[arg(long)]
+    kitty_unicode_placeholders: bool,
+
+    /// Factor to scale the Pokemon sprite by, e.g. `2x`
+    ///
+    /// Integer factors keep pixel art crisp by using nearest-neighbour scaling. Non-integer factors
+    /// like `1.5x` resample the sprite with `--scale-filter`.
+    ///
+    /// Defaults to the largest integer factor that fits within `--max-sprite-dimension`, when the
+    /// terminal reports the pixel size of its cells.
+    #[arg(long)]
+    scale: Option<Scale>,
+
+    /// Resampling filter used when scaling the Pokemon sprite with `--scale`
+    #[arg(long, default_value = "nearest")]
+    scale_filter: ScaleFilter,
+
+    /// Width of the terminal in columns, used to fit the sprite and wrap the message
+    ///
+    /// Can also be set via the `COLUMNS` environment variable.
+    ///
+    /// Detected from the terminal by default.
+    #[arg(long, env = "COLUMNS")]
+    columns: Option<u16>,
+
+    /// Height of the terminal in rows, used to fit the sprite above the message
+    ///
+    /// Can also be set via the `LINES` environment variable.
+    ///
+    /// Detected from the terminal by default. When too small to fit a sprite, only the message
+    /// is displayed.
+    #[arg(long, env = "LINES")]
+    rows: Option<u16>,
+
+    /// Specifies when to use colors and graphics escape sequences
+    ///
+    /// `auto`: Enabled when stdout is a terminal and `NO_COLOR` isn't set.
+    ///
+    /// `always`: Always enabled, e.g. when piping into `less -R`.
+    ///
+    /// `never`: Never enabled, the sprite is drawn with `--protocol ascii` unless another protocol
+    ///          is specified.
+    #[arg(long, default_value = "auto")]
+    color: ColorMode,
+
+    /// Background color to blend the Pokemon sprite's transparent pixels onto
+    ///
+    /// `none`: Keeps the sprite transparent.
+    ///
+    /// `auto`: Queries the terminal for its background color, keeping the sprite transparent if
+    ///         the terminal doesn't answer.
+    ///
+    /// `#RRGGBB`: Uses the given color, useful for terminals without transparency support.
+    #[arg(long, default_value = "none")]
+    background: Background,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Play "Who's that Pokemon?", guessing Pokemon from their silhouettes
+    Quiz(QuizOpt),
+    /// Show the Pokedex of Pokemon encountered so far
+    Dex(DexOpt),
+    /// Throw Poke Balls at a wild Pokemon to catch it
+    Catch,
+    /// List the Pokemon caught so far
+    Box,
+    /// Show your buddy Pokemon, which gains experience and evolves every time it's shown
+    Buddy(BuddyOpt),
+    /// Battle two random Pokemon against each other
+    Battle(BattleOpt),
+    /// Inspect the configuration file
+    Config(ConfigOpt),
+    /// Manage the cache of sprites
+    Cache(CacheOpt),
+}
+
+#[derive(Args, Debug)]
+struct CacheOpt {
+    #[command(subcommand)]
+    command: CacheCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    /// Download the sprites of every Pokemon into the cache, so that they're available offline
+    Prefetch(PrefetchOpt),
+}
+
+#[derive(Args, Debug)]
+struct PrefetchOpt {
+    /// Only download the sprites of the given generations, e.g. `--generation 1,2`
+    #[arg(long = "generation", value_delimiter = ',')]
+    generations: Vec<i64>,
+}
+
+#[derive(Args, Debug)]
+struct ConfigOpt {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Print the effective value of every option and where it came from, e.g. the command line
+    /// or the profile
+    Show,
+}
+
+#[derive(Args, Debug)]
+struct BattleOpt {
+    /// Seed for picking the Pokemon, their moves and the outcome of every turn
+    ///
+    /// The same seed always plays out the same battle. A random battle is played by default.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Level of both Pokemon
+    #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(i64).range(1..=100))]
+    level: i64,
+}
+
+#[derive(Args, Debug)]
+struct BuddyOpt {
+    /// Pokemon to choose as your buddy, replacing the current one
+    ///
+    /// Can be a Pokedex number or a name in any language, e.g. `25` or `pikachu`. A random
+    /// Pokemon is chosen when you don't have a buddy yet.
+    #[arg(long)]
+    pokemon: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct QuizOpt {
+    /// Number of Pokemon to guess
+    #[arg(long, default_value_t = 1)]
+    rounds: u32,
+
+    /// Hints displayed along with the silhouette
+    ///
+    /// `first-letter`: The first letter of the Pokemon's name.
+    ///
+    /// `type`: The Pokemon's types.
+    ///
+    /// `generation`: The generation the Pokemon was introduced in.
     ///
-    /// For terminals that support Kitty, iTerm2, or Sixel graphics protocols, defaults to `30`.
-    #[arg(long, default_value = {if viuer::get_kitty_support() != viuer::KittySupport::None || viuer::is_iterm_supported() || viuer::is_sixel_supported() { "30" } else { "0" }})]
-    max_sprite_dimension: u32,
+    /// Can be given multiple times or as a comma separated list, e.g. `--hint type,generation`.
+    #[arg(long = "hint", value_delimiter = ',')]
+    hints: Vec<QuizHint>,
+}
+
+#[derive(Args, Debug)]
+struct DexOpt {
+    /// Only show the given generations, e.g. `--generation 1,2`
+    #[arg(long = "generation", value_delimiter = ',')]
+    generations: Vec<i64>,
+
+    /// Whether to show the sprites of the Pokemon, with silhouettes for those not seen yet
+    ///
+    /// Otherwise, each Pokemon is shown as `●` when seen, `★` when seen shiny and `·` when not
+    /// seen yet.
+    #[arg(long)]
+    sprites: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum QuizHint {
+    FirstLetter,
+    Type,
+    Generation,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Ball {
+    Poke,
+    Great,
+    Ultra,
+}
+
+impl Ball {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Poke => "Poké Ball",
+            Self::Great => "Great Ball",
+            Self::Ultra => "Ultra Ball",
+        }
+    }
+
+    fn catch_rate_modifier(self) -> f64 {
+        match self {
+            Self::Poke => 1.0,
+            Self::Great => 1.5,
+            Self::Ultra => 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gender {
+    Male,
+    Female,
+    Genderless,
+}
+
+impl Gender {
+    /// Picks a gender using the species' chance of being female, in eighths
+    fn random(gender_rate: i64) -> Self {
+        if gender_rate < 0 {
+            Self::Genderless
+        } else if rand::random_range(0..8) < gender_rate {
+            Self::Female
+        } else {
+            Self::Male
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Male => "♂",
+            Self::Female => "♀",
+            Self::Genderless => "",
+        }
+    }
+}
+
+/// Values for each of a Pokemon's stats, e.g. its base stats or IVs
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub(crate) struct Stats {
+    pub hp: i64,
+    pub attack: i64,
+    pub defense: i64,
+    pub special_attack: i64,
+    pub special_defense: i64,
+    pub speed: i64,
+}
+
+impl Stats {
+    fn random_ivs() -> Self {
+        Self {
+            hp: rand::random_range(0..=MAX_IV),
+            attack: rand::random_range(0..=MAX_IV),
+            defense: rand::random_range(0..=MAX_IV),
+            special_attack: rand::random_range(0..=MAX_IV),
+            special_defense: rand::random_range(0..=MAX_IV),
+            speed: rand::random_range(0..=MAX_IV),
+        }
+    }
+}
+
+/// Collects stats from their PokeAPI names, e.g. `special-attack`
+impl<'a> FromIterator<(&'a str, i64)> for Stats {
+    fn from_iter<I: IntoIterator<Item = (&'a str, i64)>>(iter: I) -> Self {
+        let mut stats = Self::default();
+        for (name, value) in iter {
+            match name {
+                "hp" => stats.hp = value,
+                "attack" => stats.attack = value,
+                "defense" => stats.defense = value,
+                "special-attack" => stats.special_attack = value,
+                "special-defense" => stats.special_defense = value,
+                "speed" => stats.speed = value,
+                _ => {}
+            }
+        }
+        stats
+    }
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -133,48 +717,611 @@ enum QueryMethod {
     Http,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GraphicsProtocol {
+    Auto,
+    Kitty,
+    Iterm,
+    Sixel,
+    Blocks,
+    Ascii,
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum CacheMode {
+    On,
+    Off,
+    ReadOnly,
+    Refresh,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    fn enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none_or(|no_color| no_color.is_empty())
+            }
+        }
+    }
+
+    /// Colors supported by the terminal, or `None` when colors are disabled
+    fn depth(self) -> Option<ColorDepth> {
+        self.enabled().then(ColorDepth::detect)
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum CropMode {
+    Bbox,
+    Square,
+    KeepAspect,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Effect {
+    Silhouette,
+    Grayscale,
+    Gameboy,
+    Sepia,
+    FlipH,
+    Outline,
+    Shadow,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ScaleFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    Lanczos3,
+}
+
+impl From<ScaleFilter> for FilterType {
+    fn from(filter: ScaleFilter) -> Self {
+        match filter {
+            ScaleFilter::Nearest => FilterType::Nearest,
+            ScaleFilter::Triangle => FilterType::Triangle,
+            ScaleFilter::CatmullRom => FilterType::CatmullRom,
+            ScaleFilter::Gaussian => FilterType::Gaussian,
+            ScaleFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Scale(f32);
+
+impl FromStr for Scale {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let factor = s.strip_suffix(['x', 'X']).unwrap_or(s).parse::<f32>()?;
+        if !factor.is_finite() || factor <= 0.0 {
+            return Err(anyhow::anyhow!("Scale must be a positive factor like `2x`"));
+        }
+        Ok(Self(factor))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Background {
+    None,
+    Auto,
+    Color(Rgb<u8>),
+}
+
+impl FromStr for Background {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "auto" => Ok(Self::Auto),
+            _ => {
+                let hex = s
+                    .strip_prefix('#')
+                    .filter(|hex| hex.len() == 6)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Background must be `none`, `auto` or `#RRGGBB`")
+                    })?;
+                let color = u32::from_str_radix(hex, 16)?;
+                let [_, r, g, b] = color.to_be_bytes();
+                Ok(Self::Color(Rgb([r, g, b])))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum SpriteRetrievalMethod {
     #[cfg(feature = "embed-sprites")]
     Embedded,
     Http,
+    Dir,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let opt = Opt::parse();
+    let config = Config::load(&Opt::command())?;
+    let matches = config.apply(Opt::command()).get_matches();
+    config.validate(&Opt::command(), &matches)?;
+    let opt = Opt::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+    match &opt.command {
+        Some(Command::Quiz(quiz_opt)) => return quiz::run(&opt, quiz_opt).await,
+        Some(Command::Dex(dex_opt)) => return dex::run(&opt, dex_opt).await,
+        Some(Command::Catch) => return catch::run(&opt).await,
+        Some(Command::Box) => return catch::run_box(&opt).await,
+        Some(Command::Buddy(buddy_opt)) => return buddy::run(&opt, buddy_opt).await,
+        Some(Command::Battle(battle_opt)) => return battle::run(&opt, battle_opt).await,
+        Some(Command::Config(ConfigOpt {
+            command: ConfigCommand::Show,
+        })) => {
+            config.show(&Opt::command(), &matches);
+            return Ok(());
+        }
+        Some(Command::Cache(CacheOpt {
+            command: CacheCommand::Prefetch(prefetch_opt),
+        })) => return cache::prefetch(&opt, prefetch_opt).await,
+        None => {}
+    }
+
+    let mut team = get_pokemon_team(&opt, opt.count).await?;
+    // Recording the encounter is only bookkeeping, so a Pokedex which can't be opened, e.g. in a
+    // read-only home directory, never prevents the greeting
+    let local_db = if opt.no_pokedex {
+        None
+    } else {
+        LocalDb::new(&opt.data_dir)
+            .await
+            .inspect_err(|err| eprintln!("Warning: Failed to open the Pokedex: {err}"))
+            .ok()
+    };
+    let mut team_context = Vec::with_capacity(team.len());
+    let mut name_colors = Vec::with_capacity(team.len());
+    for pokemon in &mut team {
+        name_colors.push((
+            pokemon.name.clone(),
+            pokemon
+                .types
+                .first()
+                .and_then(|primary_type| type_color(primary_type)),
+        ));
+        let gender = Gender::random(pokemon.gender_rate);
+        pokemon.female = gender == Gender::Female && pokemon.has_gender_differences;
+        let new = match &local_db {
+            Some(local_db) => local_db
+                .record_encounter(pokemon.species_id, pokemon.shiny)
+                .await
+                .inspect_err(|err| eprintln!("Warning: Failed to record the encounter: {err}"))
+                .unwrap_or(false),
+            None => false,
+        };
+        team_context.push(WildPokemonContext {
+            pokemon: pokemon.name.clone(),
+            number: pokemon.species_id,
+            types: pokemon.types.clone(),
+            shiny: pokemon.shiny,
+            new,
+            level: rand::random_range(WILD_LEVELS),
+            gender: gender.symbol(),
+            nature: NATURES[rand::random_range(0..NATURES.len())],
+            ivs: Stats::random_ivs(),
+        });
+    }
+
+    let color_depth = opt.color.depth();
+    let border_style = match (opt.type_colored_bubble, color_depth, name_colors[0].1) {
+        (true, Some(depth), Some(primary_type_color)) => Some(depth.foreground(primary_type_color)),
+        _ => None,
+    };
+
+    let mut tt = TinyTemplate::new();
+    // The message is displayed in a terminal, so HTML escaping would only garble names like
+    // Farfetch'd
+    tt.set_default_formatter(&tinytemplate::format_unescaped);
+    const TEMPLATE_NAME: &str = "pokemonsay";
+    let piped_in_template = {
+        let stdin = io::stdin();
+        if stdin.is_terminal() {
+            None
+        } else {
+            let mut line = String::new();
+            let mut handle = stdin.lock();
+            handle.read_line(&mut line)?;
+            Some(String::from(line.trim()))
+        }
+    };
+    let template = match (piped_in_template, &opt.template_file, &opt.template_dir) {
+        (Some(piped_in_template), _, _) => Template::inline(piped_in_template),
+        (None, Some(template_file), _) => Template::read(template_file)?,
+        (None, None, Some(template_dir)) => Template::pick(template_dir)?,
+        (None, None, None) => Template::inline(opt.pokemonsay_template.clone()),
+    };
+    template::add_template(&mut tt, TEMPLATE_NAME, &template, &name_colors, color_depth)?;
+    let first = team_context[0].clone();
+    let names = team_context
+        .iter()
+        .map(|pokemon| pokemon.pokemon.as_str())
+        .collect::<Vec<_>>();
+    let context = PokemonsayTemplateContext {
+        variables: variables::collect(&opt.vars)?,
+        pokemon: join_names(&names),
+        number: first.number,
+        types: first.types,
+        shiny: team_context.iter().any(|pokemon| pokemon.shiny),
+        new: team_context.iter().any(|pokemon| pokemon.new),
+        level: first.level,
+        gender: first.gender,
+        nature: first.nature,
+        ivs: first.ivs,
+        team: team_context,
+    };
+    let rendered = tt.render(TEMPLATE_NAME, &context)?;
+
+    let terminal_size = terminal::size(opt.columns, opt.rows)
+        .map(|(columns, rows)| (u32::from(columns), u32::from(rows)));
+    let bubble = bubble::render(
+        &rendered,
+        terminal_size.map(|(columns, _)| columns as usize),
+        border_style.as_deref(),
+    );
+
+    // Leave a row for the prompt after the output
+    let sprite_bounds = terminal_size
+        .map(|(columns, rows)| (columns, rows.saturating_sub(bubble.len() as u32 + 1)));
+    let text_only = sprite_bounds
+        .is_some_and(|(columns, rows)| columns < MIN_SPRITE_COLUMNS || rows < MIN_SPRITE_ROWS);
+
+    let graphics = new_graphics(&opt);
+    let names = team
+        .iter()
+        .map(|pokemon| pokemon.name.as_str())
+        .collect::<Vec<_>>();
+    if opt.show_evolutions {
+        let with_sprites = graphics.protocol() != GraphicsProtocol::Text && !text_only;
+        let columns = sprite_bounds.map_or(DEFAULT_COLUMNS, |(columns, _)| columns);
+        evolution::print_chain(&opt, &graphics, &team[0], with_sprites, columns).await?;
+    } else if opt.card {
+        let with_sprites = graphics.protocol() != GraphicsProtocol::Text && !text_only;
+        card::print(&opt, &graphics, &team[0], with_sprites, sprite_bounds).await?;
+    } else if let Some(export) = &opt.export {
+        let columns = sprite_bounds.map_or(DEFAULT_COLUMNS, |(columns, _)| columns);
+        export_sprites(&opt, &team, columns, export).await?;
+    } else if graphics.protocol() == GraphicsProtocol::Text {
+        println!("{}", names.join(", "));
+    } else if !text_only {
+        if let [pokemon] = &team[..] {
+            let sprite_image = get_pokemon_sprite(&opt, pokemon).await?;
+            print_sprite(&opt, &graphics, sprite_image, sprite_bounds)?;
+        } else {
+            let mut sprites = Vec::with_capacity(team.len());
+            for pokemon in &team {
+                let sprite_image = get_pokemon_sprite(&opt, pokemon).await?;
+                sprites.push(apply_sprite_options(&opt, sprite_image));
+            }
+            let columns = sprite_bounds.map_or(DEFAULT_COLUMNS, |(columns, _)| columns);
+            let rows = sprite_bounds.map(|(_, rows)| rows);
+            print_sprite_grid(
+                &opt,
+                &graphics,
+                &sprites,
+                &names,
+                TEAM_SPRITE_COLUMNS,
+                columns,
+                rows,
+            )?;
+        }
+    }
+
+    for line in bubble {
+        println!("{line}");
+    }
+
+    Ok(())
+}
 
-    let pokemon = match opt.query_method {
+async fn get_pokemon(opt: &Opt) -> anyhow::Result<Pokemon> {
+    get_pokemon_team(opt, 1)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Pokemon not found!"))
+}
+
+/// Picks `count` distinct random Pokemon with a single query
+async fn get_pokemon_team(opt: &Opt, count: u32) -> anyhow::Result<Vec<Pokemon>> {
+    // Like recording them, avoiding recent encounters is best-effort
+    let excluded_species_ids = if opt.no_repeat > 0 {
+        let recent_species = match LocalDb::new(&opt.data_dir).await {
+            Ok(local_db) => local_db.get_recent_species(opt.no_repeat).await,
+            Err(err) => Err(err),
+        };
+        recent_species
+            .inspect_err(|err| eprintln!("Warning: Failed to read the recent encounters: {err}"))
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    let mut team = match opt.query_method {
         QueryMethod::Db => {
+            // Custom queries written before the parameters were bound silently ignore them
+            if opt.no_repeat > 0 && !opt.db_pokemon_query.contains("$1") {
+                eprintln!(
+                    "Warning: `--db-pokemon-query` doesn't use `$1`, so `--no-repeat` is ignored"
+                );
+            }
+            if count > 1 && !opt.db_pokemon_query.contains("$2") {
+                eprintln!(
+                    "Warning: `--db-pokemon-query` doesn't use `$2`, so it may not pick `--count` Pokemon"
+                );
+            }
             let db = Db::new(&opt.db_path).await?;
             db.get_pokemon(
                 &opt.db_pokemon_query,
                 &opt.db_species_name_query,
                 &opt.db_sprites_query,
+                &excluded_species_ids,
+                count,
             )
             .await?
         }
         QueryMethod::Http => {
-            let http = Http::new();
-            http.get_pokemon(opt.http_graphql_query).await?
+            let http = Http::new(opt.cache);
+            http.get_pokemon(&opt.http_graphql_query, &excluded_species_ids, count)
+                .await?
+        }
+    };
+    for pokemon in &mut team {
+        pokemon.shiny = rand::random_ratio(1, opt.shiny_odds);
+    }
+    Ok(team)
+}
+
+async fn get_pokemon_details(opt: &Opt, pokemon: &Pokemon) -> anyhow::Result<PokemonDetails> {
+    match opt.query_method {
+        QueryMethod::Db => {
+            let db = Db::new(&opt.db_path).await?;
+            db.get_pokemon_details(pokemon).await
+        }
+        QueryMethod::Http => {
+            let http = Http::new(opt.cache);
+            http.get_pokemon_details(pokemon).await
+        }
+    }
+}
+
+async fn get_pokemon_by_species(opt: &Opt, species_id: i64) -> anyhow::Result<Pokemon> {
+    match opt.query_method {
+        QueryMethod::Db => {
+            let db = Db::new(&opt.db_path).await?;
+            db.get_pokemon_by_species(
+                species_id,
+                &opt.db_species_name_query,
+                &opt.db_sprites_query,
+            )
+            .await
+        }
+        QueryMethod::Http => {
+            let http = Http::new(opt.cache);
+            http.get_pokemon_by_species(species_id).await
+        }
+    }
+}
+
+async fn find_species_id(opt: &Opt, name: &str) -> anyhow::Result<i64> {
+    let species_id = match opt.query_method {
+        QueryMethod::Db => {
+            let db = Db::new(&opt.db_path).await?;
+            db.find_species_id(name).await?
+        }
+        QueryMethod::Http => {
+            let http = Http::new(opt.cache);
+            http.find_species_id(name).await?
+        }
+    };
+    species_id.ok_or_else(|| anyhow::anyhow!("Pokemon {name} not found!"))
+}
+
+async fn get_evolutions(opt: &Opt, species_id: i64) -> anyhow::Result<Vec<Evolution>> {
+    match opt.query_method {
+        QueryMethod::Db => {
+            let db = Db::new(&opt.db_path).await?;
+            db.get_evolutions(species_id).await
+        }
+        QueryMethod::Http => {
+            let http = Http::new(opt.cache);
+            http.get_evolutions(species_id).await
+        }
+    }
+}
+
+async fn get_evolution_chain(
+    opt: &Opt,
+    species_id: i64,
+) -> anyhow::Result<Vec<EvolutionChainLink>> {
+    let chain = match opt.query_method {
+        QueryMethod::Db => {
+            let db = Db::new(&opt.db_path).await?;
+            db.get_evolution_chain(species_id).await?
+        }
+        QueryMethod::Http => {
+            let http = Http::new(opt.cache);
+            http.get_evolution_chain(species_id).await?
         }
     };
+    if chain.is_empty() {
+        return Err(anyhow::anyhow!("Evolution chain not found!"));
+    }
+    Ok(chain)
+}
 
+async fn get_card_details(opt: &Opt, pokemon: &Pokemon) -> anyhow::Result<CardDetails> {
+    match opt.query_method {
+        QueryMethod::Db => {
+            let db = Db::new(&opt.db_path).await?;
+            db.get_card_details(pokemon).await
+        }
+        QueryMethod::Http => {
+            let http = Http::new(opt.cache);
+            http.get_card_details(pokemon).await
+        }
+    }
+}
+
+async fn get_battle_details(opt: &Opt, pokemon: &Pokemon) -> anyhow::Result<BattleDetails> {
+    match opt.query_method {
+        QueryMethod::Db => {
+            let db = Db::new(&opt.db_path).await?;
+            db.get_battle_details(pokemon).await
+        }
+        QueryMethod::Http => {
+            let http = Http::new(opt.cache);
+            http.get_battle_details(pokemon).await
+        }
+    }
+}
+
+async fn get_type_efficacy(opt: &Opt) -> anyhow::Result<TypeEfficacy> {
+    match opt.query_method {
+        QueryMethod::Db => {
+            let db = Db::new(&opt.db_path).await?;
+            db.get_type_efficacy().await
+        }
+        QueryMethod::Http => {
+            let http = Http::new(opt.cache);
+            http.get_type_efficacy().await
+        }
+    }
+}
+
+async fn get_species(opt: &Opt) -> anyhow::Result<Vec<Species>> {
+    match opt.query_method {
+        QueryMethod::Db => {
+            let db = Db::new(&opt.db_path).await?;
+            db.get_species().await
+        }
+        QueryMethod::Http => {
+            let http = Http::new(opt.cache);
+            http.get_species().await
+        }
+    }
+}
+
+/// Gets the Pokemon's sprite, using the shiny and female variants when they apply and are
+/// available
+async fn get_pokemon_sprite(opt: &Opt, pokemon: &Pokemon) -> anyhow::Result<DynamicImage> {
+    let variants = [
+        (pokemon.shiny && pokemon.female, "shiny/female/"),
+        (pokemon.shiny, "shiny/"),
+        (pokemon.female, "female/"),
+    ];
+    for (_, variant) in variants.into_iter().filter(|(applies, _)| *applies) {
+        if let Some(variant_sprite_url) = sprite_variant_url(&pokemon.sprite_url, variant)
+            && let Ok(sprite_image) = get_sprite(opt, &variant_sprite_url).await
+        {
+            return Ok(sprite_image);
+        }
+    }
+    get_sprite(opt, &pokemon.sprite_url).await
+}
+
+// Sprites are served from this repository, whose paths are mirrored by a checkout with
+// `--sprites-retrieval-method dir`
+const SPRITES_REPOSITORY_URL: &str = "https://raw.githubusercontent.com/PokeAPI/sprites/master/";
+
+/// PokeAPI keeps the sprite variants, e.g. `shiny/` or `female/`, in directories next to the
+/// default ones
+fn sprite_variant_url(sprite_url: &str, variant: &str) -> Option<String> {
+    const SPRITES_DIR: &str = "/sprites/pokemon/";
+    sprite_url
+        .contains(SPRITES_DIR)
+        .then(|| sprite_url.replacen(SPRITES_DIR, &format!("{SPRITES_DIR}{variant}"), 1))
+}
+
+async fn get_sprite(opt: &Opt, url: &str) -> anyhow::Result<DynamicImage> {
     let sprite_bytes = match opt.sprites_retrieval_method {
         #[cfg(feature = "embed-sprites")]
-        SpriteRetrievalMethod::Embedded => Db::get_sprites(&pokemon.sprite_url)?,
+        SpriteRetrievalMethod::Embedded => Db::get_sprites(url)?,
         SpriteRetrievalMethod::Http => {
-            let http = Http::new();
-            http.get_sprite(&pokemon.sprite_url).await?
+            let http = Http::new(opt.cache).with_sprite_cache_size(opt.sprite_cache_size);
+            http.get_sprite(url).await?
+        }
+        SpriteRetrievalMethod::Dir => {
+            let sprites_dir = opt
+                .sprites_dir
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Sprites directory not found!"))?;
+            let path = url
+                .strip_prefix(SPRITES_REPOSITORY_URL)
+                .map(|path| sprites_dir.join(path))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Sprite {url} isn't from the PokeAPI sprites repository!")
+                })?;
+            bytes::Bytes::from(tokio::fs::read(&path).await.map_err(|err| {
+                anyhow::anyhow!("Failed to read sprite {}: {err}", path.display())
+            })?)
         }
     };
 
-    let mut sprite_image = image::load_from_memory(sprite_bytes.as_ref())?;
+    Ok(image::load_from_memory(sprite_bytes.as_ref())?)
+}
+
+fn new_graphics(opt: &Opt) -> Graphics {
+    Graphics::new(
+        opt.protocol,
+        opt.kitty_unicode_placeholders,
+        opt.color.enabled(),
+        match opt.background {
+            Background::None => None,
+            // Querying the terminal writes escape sequences, so skip it when they aren't wanted
+            Background::Auto if !opt.color.enabled() => None,
+            Background::Auto => terminal::background_color(),
+            Background::Color(color) => Some(color),
+        },
+    )
+}
 
+/// Crops the sprite and applies its effects as configured
+fn apply_sprite_options(opt: &Opt, mut sprite_image: DynamicImage) -> DynamicImage {
     if opt.crop_sprite_transparent_bg {
-        sprite_image = crate::image_util::crop_transparent_pixels(&sprite_image);
+        sprite_image = crate::image_util::crop_transparent_pixels(
+            &sprite_image,
+            opt.crop_alpha_threshold,
+            opt.crop_padding,
+            opt.crop_mode,
+        );
+    }
+
+    for &effect in &opt.effects {
+        sprite_image = crate::image_util::apply_effect(&sprite_image, effect);
     }
 
+    sprite_image
+}
+
+/// Prints the sprite as configured, returning its dimensions in terminal cells
+fn print_sprite(
+    opt: &Opt,
+    graphics: &Graphics,
+    sprite_image: DynamicImage,
+    bounds: Option<(u32, u32)>,
+) -> anyhow::Result<(u32, u32)> {
+    let mut sprite_image = apply_sprite_options(opt, sprite_image);
+
     let mut viuer_config = viuer::Config {
         transparent: true,
         absolute_offset: false,
@@ -182,57 +1329,338 @@ async fn main() -> anyhow::Result<()> {
         ..Default::default()
     };
 
-    // TODO: Check if PNG DPI needs reading to figure out how to set max size appropriately
+    let max_sprite_dimension =
+        opt.max_sprite_dimension
+            .unwrap_or(if graphics.protocol() == GraphicsProtocol::Blocks {
+                0
+            } else {
+                30
+            });
+
     let (width, height) = sprite_image.dimensions();
-    if opt.max_sprite_dimension > 0 {
+    let scale = match (opt.scale, graphics.cell_size()) {
+        (Some(scale), _) => Some(scale),
+        // Use the largest integer factor that fits to keep the pixel art crisp
+        (None, Some((cell_width, cell_height))) if max_sprite_dimension > 0 => {
+            let factor = (max_sprite_dimension * cell_width / width)
+                .min(max_sprite_dimension * cell_height / height);
+            (factor > 0).then_some(Scale(factor as f32))
+        }
+        _ => None,
+    };
+
+    if let Some(scale) = scale {
+        sprite_image = scale_sprite(opt, &sprite_image, scale);
+        let (columns, rows);
+        (sprite_image, (columns, rows)) = crate::image_util::pad_to_cells(
+            &sprite_image,
+            graphics.cell_size().unwrap_or(DEFAULT_CELL_SIZE),
+        );
+        viuer_config.width = Some(columns);
+        viuer_config.height = Some(rows);
+    } else if max_sprite_dimension > 0 {
         if width >= height {
-            viuer_config.width = Some(width.min(opt.max_sprite_dimension));
+            viuer_config.width = Some(width.min(max_sprite_dimension));
         } else if height >= width {
-            viuer_config.height = Some(height.min(opt.max_sprite_dimension));
+            viuer_config.height = Some(height.min(max_sprite_dimension));
         }
     }
 
-    viuer::print(&sprite_image, &viuer_config)?;
+    if let Some(bounds) = bounds {
+        let dimensions = match (viuer_config.width, viuer_config.height) {
+            (None, None) => fit_dimensions(
+                sprite_image.width(),
+                sprite_image.height(),
+                bounds.0,
+                bounds.1,
+            ),
+            (width, height) => find_best_fit(sprite_image.dimensions(), width, height),
+        };
+        let (width, height) = fit_within(dimensions, bounds);
+        viuer_config.width = Some(width);
+        viuer_config.height = Some(height);
+    }
 
-    let mut tt = TinyTemplate::new();
-    const TEMPLATE_NAME: &str = "pokemonsay";
-    let piped_in_template = {
-        let stdin = io::stdin();
-        if stdin.is_terminal() {
-            None
-        } else {
-            let mut line = String::new();
-            let mut handle = stdin.lock();
-            handle.read_line(&mut line)?;
-            Some(String::from(line.trim()))
-        }
+    graphics.print(&sprite_image, &viuer_config)
+}
+
+/// Scales the sprite by the factor, keeping pixel art crisp for integer factors
+fn scale_sprite(opt: &Opt, sprite_image: &DynamicImage, Scale(factor): Scale) -> DynamicImage {
+    let filter = if factor.fract() == 0.0 {
+        FilterType::Nearest
+    } else {
+        opt.scale_filter.into()
     };
-    let template = piped_in_template.unwrap_or(opt.pokemonsay_template);
-    tt.add_template(TEMPLATE_NAME, &template)?;
-    let context = PokemonsayTemplateContext {
-        pokemon: pokemon.name.to_uppercase(),
+    crate::image_util::scale(sprite_image, factor, filter)
+}
+
+/// Prints the sprites side by side in cells `cell_columns` wide, or as wide as the sprites once
+/// scaled by `--scale`, wrapping to more rows to fit within `columns`, with their names
+/// underneath. When given, the sprites are shrunk to fit within `rows` too.
+fn print_sprite_grid(
+    opt: &Opt,
+    graphics: &Graphics,
+    sprites: &[DynamicImage],
+    names: &[&str],
+    cell_columns: u32,
+    columns: u32,
+    rows: Option<u32>,
+) -> anyhow::Result<()> {
+    let cell_size = graphics.cell_size().unwrap_or(DEFAULT_CELL_SIZE);
+    let cell_columns = match opt.scale {
+        Some(Scale(factor)) => {
+            let sprite_width = sprites.iter().map(|sprite| sprite.width()).max();
+            ((sprite_width.unwrap_or(0) as f32 * factor) as u32)
+                .div_ceil(cell_size.0)
+                .max(1)
+        }
+        None => cell_columns,
     };
-    let rendered = tt.render(TEMPLATE_NAME, &context)?;
+    let sprites_per_row = (columns / cell_columns).max(1) as usize;
+    // Each row of sprites gets an even share of the height, minus a row for the names
+    let rows_per_row = rows.map(|rows| {
+        (rows / sprites.len().div_ceil(sprites_per_row) as u32)
+            .saturating_sub(1)
+            .max(1)
+    });
 
-    // TODO: Render the box based on the size of the rendered sprite,
-    // centering the rendered text
-    let rendered_len = rendered.len();
-    const PADDING: usize = 8;
-    assert!(PADDING.is_multiple_of(2));
-    println!("◓{}◓", "═".repeat(rendered_len + PADDING));
-    println!("‖{}‖", " ".repeat(rendered_len + PADDING));
-    println!(
-        "‖{padding}{rendered}{padding}‖",
-        padding = " ".repeat(PADDING / 2)
-    );
-    println!("‖{}‖", " ".repeat(rendered_len + PADDING));
-    println!("◓{}◓", "═".repeat(rendered_len + PADDING));
+    for (row, names) in sprites
+        .chunks(sprites_per_row)
+        .zip(names.chunks(sprites_per_row))
+    {
+        let grid = crate::image_util::tile(row, sprites_per_row as u32);
+        let (grid, dimensions) = match opt.scale {
+            Some(scale) => {
+                crate::image_util::pad_to_cells(&scale_sprite(opt, &grid, scale), cell_size)
+            }
+            None => {
+                let dimensions = find_best_fit(
+                    grid.dimensions(),
+                    Some(row.len() as u32 * cell_columns),
+                    None,
+                );
+                (grid, dimensions)
+            }
+        };
+        let (width, height) = match rows_per_row {
+            Some(rows) => fit_within(dimensions, (columns, rows)),
+            None => dimensions,
+        };
+        let viuer_config = viuer::Config {
+            transparent: true,
+            absolute_offset: false,
+            premultiplied_alpha: true,
+            width: Some(width),
+            height: Some(height),
+            ..Default::default()
+        };
+        graphics.print(&grid, &viuer_config)?;
+
+        let name_columns = (width / row.len() as u32).max(1) as usize;
+        let names = names
+            .iter()
+            .map(|name| {
+                let name = name
+                    .chars()
+                    .take(name_columns.saturating_sub(1))
+                    .collect::<String>();
+                format!("{name:^name_columns$}")
+            })
+            .collect::<String>();
+        println!("{}", names.trim_end());
+    }
 
     Ok(())
 }
 
+/// Saves the sprites to a PNG image, laid out like `print_sprite_grid` but without the names
+async fn export_sprites(
+    opt: &Opt,
+    team: &[Pokemon],
+    columns: u32,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let mut sprites = Vec::with_capacity(team.len());
+    for pokemon in team {
+        let mut sprite_image = apply_sprite_options(opt, get_pokemon_sprite(opt, pokemon).await?);
+        if let Some(scale) = opt.scale {
+            sprite_image = scale_sprite(opt, &sprite_image, scale);
+        }
+        sprites.push(sprite_image);
+    }
+
+    let sprites_per_row = (columns / TEAM_SPRITE_COLUMNS).max(1);
+    let mut image = crate::image_util::tile(&sprites, sprites_per_row);
+    // `auto` is the terminal's background, which has nothing to do with where the image is viewed
+    if let Background::Color(color) = opt.background {
+        image = crate::image_util::composite_background(&image, color);
+    }
+    image
+        .save_with_format(path, ImageFormat::Png)
+        .map_err(|err| anyhow::anyhow!("Failed to export the sprites to {}: {err}", path.display()))
+}
+
+/// Base directory of the XDG specification given by the environment variable, or its default in
+/// the home directory. Unlike `dirs`, this follows the specification on every platform, as command
+/// line tools are expected to use `~/.config` rather than `~/Library/Application Support` on macOS.
+fn xdg_dir(env_var: &str, default: &str) -> Option<PathBuf> {
+    env::var_os(env_var)
+        .map(PathBuf::from)
+        // Relative paths are invalid and must be ignored
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home_dir| home_dir.join(default)))
+}
+
+/// Joins names like a sentence, e.g. `PIKACHU, EEVEE and MEW`
+fn join_names(names: &[&str]) -> String {
+    match names {
+        [] => String::new(),
+        [name] => String::from(*name),
+        [names @ .., last] => format!("{} and {last}", names.join(", ")),
+    }
+}
+
+fn roman_numeral(mut n: i64) -> String {
+    const NUMERALS: [(i64, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+
+    let mut numeral = String::new();
+    for (value, symbol) in NUMERALS {
+        while n >= value {
+            numeral.push_str(symbol);
+            n -= value;
+        }
+    }
+    numeral
+}
+
+fn title_case(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Turns a slug like `thunder-stone` into `Thunder Stone`
+fn title_case_words(slug: &str) -> String {
+    slug.split('-')
+        .map(title_case)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct Pokemon {
+    pub id: i64,
+    pub species_id: i64,
     pub name: String,
     pub sprite_url: String,
+    /// Types in slot order, e.g. `["grass", "poison"]`
+    pub types: Vec<String>,
+    pub shiny: bool,
+    /// Whether to use the female sprite variant, for female Pokemon whose sprite differs
+    pub female: bool,
+    /// Chance of the Pokemon being female in eighths, or `-1` when it's genderless
+    pub gender_rate: i64,
+    /// Whether females look different from males
+    pub has_gender_differences: bool,
 }
+
+#[derive(Debug, Clone)]
+pub(crate) struct Species {
+    pub id: i64,
+    pub generation: i64,
+    pub name: String,
+    pub sprite_url: String,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PokemonDetails {
+    /// Names of the Pokemon's species in every available language
+    pub names: Vec<String>,
+    pub generation: i64,
+    /// Chance of catching the Pokemon, from 3 (hardest) to 255 (easiest)
+    pub capture_rate: i64,
+    /// Id of the curve of experience needed to reach each level
+    pub growth_rate: i64,
+    /// Experience gained for defeating the Pokemon, missing for some Pokemon of the newest games
+    pub base_experience: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Evolution {
+    /// Id of the species evolved into
+    pub species_id: i64,
+    /// How the evolution is triggered, e.g. `level-up` or `use-item`
+    pub trigger: String,
+    pub min_level: Option<i64>,
+    pub min_happiness: Option<i64>,
+    /// Item used or held to evolve, e.g. `thunder-stone`
+    pub item: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct EvolutionChainLink {
+    pub species: Species,
+    pub evolves_from_species_id: Option<i64>,
+    /// Ways the species can be evolved into, empty for the first species of the chain
+    pub evolutions: Vec<Evolution>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CardDetails {
+    /// Category of the species in English, e.g. `Mouse Pokémon`
+    pub genus: String,
+    /// Height in decimetres
+    pub height: i64,
+    /// Weight in hectograms
+    pub weight: i64,
+    /// Abilities ordered by slot, with the hidden ability last
+    pub abilities: Vec<Ability>,
+    pub base_stats: Stats,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Ability {
+    /// e.g. `lightning-rod`
+    pub name: String,
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct BattleDetails {
+    pub base_stats: Stats,
+    /// Moves the Pokemon can learn that deal damage, ordered by id
+    pub moves: Vec<Move>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Move {
+    pub name: String,
+    pub power: i64,
+    /// Chance of hitting in percent, or `None` when the move never misses
+    pub accuracy: Option<i64>,
+    pub priority: i64,
+    /// Type of the move, e.g. `fire`
+    pub move_type: String,
+    /// Whether the move is `physical` or `special`
+    pub damage_class: String,
+}
+
+/// Damage factors in percent keyed by attacking and defending type, e.g. `("fire", "grass")` is
+/// `200`, with `100` left out
+pub(crate) type TypeEfficacy = HashMap<(String, String), i64>;
@@ -2,8 +2,18 @@
 
 pub mod prelude;
 
+pub mod pokemon_v2_ability;
+pub mod pokemon_v2_evolutionchain;
+pub mod pokemon_v2_evolutiontrigger;
+pub mod pokemon_v2_item;
 pub mod pokemon_v2_language;
 pub mod pokemon_v2_pokemon;
+pub mod pokemon_v2_pokemonability;
+pub mod pokemon_v2_pokemonevolution;
 pub mod pokemon_v2_pokemonspecies;
 pub mod pokemon_v2_pokemonspeciesname;
 pub mod pokemon_v2_pokemonsprites;
+pub mod pokemon_v2_pokemonstat;
+pub mod pokemon_v2_pokemontype;
+pub mod pokemon_v2_stat;
+pub mod pokemon_v2_type;
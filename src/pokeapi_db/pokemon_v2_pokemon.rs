@@ -28,8 +28,14 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     PokemonV2Pokemonspecies,
+    #[sea_orm(has_many = "super::pokemon_v2_pokemonability::Entity")]
+    PokemonV2Pokemonability,
     #[sea_orm(has_many = "super::pokemon_v2_pokemonsprites::Entity")]
     PokemonV2Pokemonsprites,
+    #[sea_orm(has_many = "super::pokemon_v2_pokemonstat::Entity")]
+    PokemonV2Pokemonstat,
+    #[sea_orm(has_many = "super::pokemon_v2_pokemontype::Entity")]
+    PokemonV2Pokemontype,
 }
 
 impl Related<super::pokemon_v2_pokemonspecies::Entity> for Entity {
@@ -38,10 +44,28 @@ impl Related<super::pokemon_v2_pokemonspecies::Entity> for Entity {
     }
 }
 
+impl Related<super::pokemon_v2_pokemonability::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PokemonV2Pokemonability.def()
+    }
+}
+
 impl Related<super::pokemon_v2_pokemonsprites::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::PokemonV2Pokemonsprites.def()
     }
 }
 
+impl Related<super::pokemon_v2_pokemonstat::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PokemonV2Pokemonstat.def()
+    }
+}
+
+impl Related<super::pokemon_v2_pokemontype::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PokemonV2Pokemontype.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
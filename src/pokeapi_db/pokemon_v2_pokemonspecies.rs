@@ -7,22 +7,51 @@ use sea_orm::entity::prelude::*;
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i64,
+    pub capture_rate: Option<i64>,
+    pub evolution_chain_id: Option<i64>,
+    pub evolves_from_species_id: Option<i64>,
+    pub gender_rate: Option<i64>,
+    pub generation_id: Option<i64>,
+    pub growth_rate_id: Option<i64>,
+    pub has_gender_differences: Option<bool>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::pokemon_v2_evolutionchain::Entity",
+        from = "Column::EvolutionChainId",
+        to = "super::pokemon_v2_evolutionchain::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    PokemonV2Evolutionchain,
     #[sea_orm(has_many = "super::pokemon_v2_pokemon::Entity")]
     PokemonV2Pokemon,
+    #[sea_orm(has_many = "super::pokemon_v2_pokemonevolution::Entity")]
+    PokemonV2Pokemonevolution,
     #[sea_orm(has_many = "super::pokemon_v2_pokemonspeciesname::Entity")]
     PokemonV2Pokemonspeciesname,
 }
 
+impl Related<super::pokemon_v2_evolutionchain::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PokemonV2Evolutionchain.def()
+    }
+}
+
 impl Related<super::pokemon_v2_pokemon::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::PokemonV2Pokemon.def()
     }
 }
 
+impl Related<super::pokemon_v2_pokemonevolution::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PokemonV2Pokemonevolution.def()
+    }
+}
+
 impl Related<super::pokemon_v2_pokemonspeciesname::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::PokemonV2Pokemonspeciesname.def()
@@ -3,9 +3,17 @@
 #![allow(unused_imports)]
 
 pub use super::{
-    pokemon_v2_language::Entity as PokemonV2Language,
+    pokemon_v2_ability::Entity as PokemonV2Ability,
+    pokemon_v2_evolutionchain::Entity as PokemonV2Evolutionchain,
+    pokemon_v2_evolutiontrigger::Entity as PokemonV2Evolutiontrigger,
+    pokemon_v2_item::Entity as PokemonV2Item, pokemon_v2_language::Entity as PokemonV2Language,
     pokemon_v2_pokemon::Entity as PokemonV2Pokemon,
+    pokemon_v2_pokemonability::Entity as PokemonV2Pokemonability,
+    pokemon_v2_pokemonevolution::Entity as PokemonV2Pokemonevolution,
     pokemon_v2_pokemonspecies::Entity as PokemonV2Pokemonspecies,
     pokemon_v2_pokemonspeciesname::Entity as PokemonV2Pokemonspeciesname,
     pokemon_v2_pokemonsprites::Entity as PokemonV2Pokemonsprites,
+    pokemon_v2_pokemonstat::Entity as PokemonV2Pokemonstat,
+    pokemon_v2_pokemontype::Entity as PokemonV2Pokemontype,
+    pokemon_v2_stat::Entity as PokemonV2Stat, pokemon_v2_type::Entity as PokemonV2Type,
 };
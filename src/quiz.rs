@@ -0,0 +1,114 @@
+use std::io::{self, BufRead, Write};
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{
+    Effect, Opt, Pokemon, PokemonDetails, QuizHint, QuizOpt, get_pokemon, get_pokemon_details,
+    get_pokemon_sprite, new_graphics, print_sprite, roman_numeral, terminal, title_case,
+};
+
+/// Rows reserved below the sprite for the question, hints and answer prompt
+const RESERVED_ROWS: u32 = 6;
+
+pub async fn run(opt: &Opt, quiz: &QuizOpt) -> anyhow::Result<()> {
+    let graphics = new_graphics(opt);
+    let sprite_bounds = terminal::size(opt.columns, opt.rows).map(|(columns, rows)| {
+        (
+            u32::from(columns),
+            u32::from(rows).saturating_sub(RESERVED_ROWS),
+        )
+    });
+
+    let stdin = io::stdin();
+    let mut score = 0;
+    for round in 1..=quiz.rounds {
+        let pokemon = get_pokemon(opt).await?;
+        let details = get_pokemon_details(opt, &pokemon).await?;
+        let sprite_image = get_pokemon_sprite(opt, &pokemon).await?;
+
+        if quiz.rounds > 1 {
+            println!("Round {round}/{}", quiz.rounds);
+        }
+        let silhouette = crate::image_util::apply_effect(&sprite_image, Effect::Silhouette);
+        print_sprite(opt, &graphics, silhouette, sprite_bounds)?;
+
+        println!("Who's that Pokémon?");
+        for hint in &quiz.hints {
+            println!("{}", format_hint(*hint, &pokemon, &details));
+        }
+        print!("> ");
+        io::stdout().flush()?;
+
+        let mut guess = String::new();
+        if stdin.lock().read_line(&mut guess)? == 0 {
+            // Stop asking when stdin is closed, but still reveal the answer
+            println!();
+        }
+
+        print_sprite(opt, &graphics, sprite_image, sprite_bounds)?;
+        println!("It's {}!", pokemon.name.to_uppercase());
+
+        if is_correct(&guess, &details.names) {
+            score += 1;
+            println!("Correct!");
+        } else {
+            println!("Wrong!");
+        }
+    }
+
+    println!("Score: {score}/{}", quiz.rounds);
+
+    Ok(())
+}
+
+fn format_hint(hint: QuizHint, pokemon: &Pokemon, details: &PokemonDetails) -> String {
+    match hint {
+        QuizHint::FirstLetter => format!(
+            "Its name starts with {}.",
+            pokemon
+                .name
+                .chars()
+                .next()
+                .unwrap_or_default()
+                .to_uppercase()
+        ),
+        QuizHint::Type => {
+            let types = pokemon
+                .types
+                .iter()
+                .map(|pokemon_type| title_case(pokemon_type))
+                .collect::<Vec<_>>();
+            format!("It is {} type.", types.join("/"))
+        }
+        QuizHint::Generation => format!(
+            "It was introduced in generation {}.",
+            roman_numeral(details.generation)
+        ),
+    }
+}
+
+/// Strips accents, case, punctuation and whitespace so that e.g. `Flabébé` matches `flabebe`
+fn normalize(s: &str) -> String {
+    s.nfkd()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn is_correct(guess: &str, names: &[String]) -> bool {
+    let guess = normalize(guess);
+    if guess.is_empty() {
+        return false;
+    }
+
+    names.iter().any(|name| {
+        let name = normalize(name);
+        // Allow more typos the longer the name is
+        let tolerance = match name.chars().count() {
+            0..=4 => 0,
+            5..=7 => 1,
+            _ => 2,
+        };
+        strsim::levenshtein(&guess, &name) <= tolerance
+    })
+}
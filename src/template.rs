@@ -0,0 +1,467 @@
+use std::{
+    cmp::Reverse,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use image::Rgb;
+use rand::seq::IndexedRandom;
+use serde_json::Value;
+use tinytemplate::{TinyTemplate, error::Error};
+use unicode_width::UnicodeWidthStr;
+
+use crate::color::{ColorDepth, RESET_FOREGROUND};
+
+// Delimits the front matter at the start of template files
+const FRONT_MATTER_DELIMITER: &str = "---";
+
+/// Message template, either given inline or read from a file of a template directory
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub text: String,
+    /// File the template was read from, with the number of lines before the template itself so
+    /// that errors point at the right line
+    file: Option<(PathBuf, usize)>,
+    /// How often the template is picked from a template directory relative to the others
+    weight: u32,
+}
+
+impl Template {
+    pub fn inline(text: String) -> Self {
+        Self {
+            text,
+            file: None,
+            weight: 1,
+        }
+    }
+
+    /// Reads a template file, which can start with front matter setting its `weight`:
+    ///
+    /// ```text
+    /// ---
+    /// weight: 3
+    /// ---
+    /// Wild {pokemon} appeared!
+    /// ```
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| anyhow::anyhow!("Failed to read template {}: {err}", path.display()))?;
+
+        let mut weight = 1;
+        let mut lines = contents.split_inclusive('\n');
+        let mut front_matter_end = 0;
+        if let Some(first_line) = lines.next()
+            && first_line.trim_end() == FRONT_MATTER_DELIMITER
+        {
+            front_matter_end = first_line.len();
+            let mut closed = false;
+            for (i, line) in lines.enumerate() {
+                front_matter_end += line.len();
+                let line = line.trim();
+                if line == FRONT_MATTER_DELIMITER {
+                    closed = true;
+                    break;
+                }
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                // The opening delimiter is on line 1
+                let location = format!("{}:{}", path.display(), i + 2);
+                match line
+                    .split_once(':')
+                    .map(|(key, value)| (key.trim(), value.trim()))
+                {
+                    Some(("weight", value)) => {
+                        weight = value.parse().map_err(|_| {
+                            anyhow::anyhow!("Invalid weight `{value}` at {location}!")
+                        })?;
+                    }
+                    _ => {
+                        return Err(anyhow::anyhow!(
+                            "Unknown front matter `{line}` at {location}!"
+                        ));
+                    }
+                }
+            }
+            if !closed {
+                return Err(anyhow::anyhow!(
+                    "Front matter of {} isn't closed with `{FRONT_MATTER_DELIMITER}`!",
+                    path.display()
+                ));
+            }
+        }
+
+        let front_matter = &contents[..front_matter_end];
+        Ok(Self {
+            text: String::from(contents[front_matter_end..].trim_end()),
+            file: Some((PathBuf::from(path), front_matter.matches('\n').count())),
+            weight,
+        })
+    }
+
+    /// Picks one of the templates of the directory at random, according to their weights
+    pub fn pick(dir: &Path) -> anyhow::Result<Self> {
+        let entries = fs::read_dir(dir).map_err(|err| {
+            anyhow::anyhow!("Failed to read template directory {}: {err}", dir.display())
+        })?;
+        let mut paths = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            // Skip hidden files like `.DS_Store` and editor swap files
+            if entry.file_type()?.is_file() && !entry.file_name().to_string_lossy().starts_with('.')
+            {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+
+        let templates = paths
+            .iter()
+            .map(|path| Self::read(path))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        templates
+            .choose_weighted(&mut rand::rng(), |template| template.weight)
+            .cloned()
+            .map_err(|_| anyhow::anyhow!("No templates to pick from in {}!", dir.display()))
+    }
+
+    /// Describes where a line of the template is, e.g. `templates/battle.txt:3`
+    fn location(&self, line: usize) -> String {
+        match &self.file {
+            Some((path, lines_before)) => format!("{}:{}", path.display(), lines_before + line),
+            None => format!("line {line}"),
+        }
+    }
+}
+
+/// Compiles the template along with the filters used by its values, pointing parse errors at the
+/// template's file and line
+pub fn add_template<'a>(
+    tt: &mut TinyTemplate<'a>,
+    name: &'a str,
+    template: &'a Template,
+    name_colors: &[(String, Option<Rgb<u8>>)],
+    depth: Option<ColorDepth>,
+) -> anyhow::Result<()> {
+    add_filters(tt, template, name_colors, depth)?;
+    tt.add_template(name, &template.text)
+        .map_err(|err| match err {
+            Error::ParseError { msg, line, column } => anyhow::anyhow!(
+                "Failed to parse the template at {}, column {column}: {msg}",
+                template.location(line)
+            ),
+            err => err.into(),
+        })
+}
+
+/// Transformation of a template value, applied with `{value | filter}` or `{value | filter:arg}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Filter {
+    Upper,
+    Lower,
+    /// Capitalizes the first letter of every word and lowercases the rest
+    Title,
+    /// Pads with spaces on the right up to the width in terminal columns
+    Pad(usize),
+    /// Formats a number with a minimum width, padded with zeros when the width starts with `0`
+    Number {
+        width: usize,
+        zeros: bool,
+    },
+    /// Colors the Pokemon names by their primary type when colors are enabled
+    TypeColor,
+}
+
+impl Filter {
+    fn parse(filter: &str) -> Option<Self> {
+        let (name, arg) = match filter.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (filter.trim(), None),
+        };
+        match (name, arg) {
+            ("upper", None) => Some(Self::Upper),
+            ("lower", None) => Some(Self::Lower),
+            ("title", None) => Some(Self::Title),
+            ("pad", Some(width)) => Some(Self::Pad(width.parse().ok()?)),
+            ("number", None) => Some(Self::Number {
+                width: 0,
+                zeros: false,
+            }),
+            ("number", Some(width)) => Some(Self::Number {
+                width: width.parse().ok()?,
+                zeros: width.starts_with('0'),
+            }),
+            ("type_color", None) => Some(Self::TypeColor),
+            _ => None,
+        }
+    }
+
+    fn apply(
+        self,
+        value: &Value,
+        name_colors: &[(String, Option<Rgb<u8>>)],
+        depth: Option<ColorDepth>,
+    ) -> tinytemplate::error::Result<Value> {
+        let text = match value {
+            Value::String(text) => text.clone(),
+            Value::Null => String::new(),
+            value => value.to_string(),
+        };
+        let filtered = match self {
+            Self::Upper => text.to_uppercase(),
+            Self::Lower => text.to_lowercase(),
+            Self::Title => text
+                .split(' ')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| {
+                            first
+                                .to_uppercase()
+                                .chain(chars.flat_map(char::to_lowercase))
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<String>>()
+                .join(" "),
+            Self::Pad(width) => {
+                let padding = width.saturating_sub(text.width());
+                format!("{text}{}", " ".repeat(padding))
+            }
+            Self::Number { width, zeros } => {
+                let number = value.as_i64().ok_or_else(|| Error::GenericError {
+                    msg: format!("Expected a number for the `number` filter but found `{text}`."),
+                })?;
+                if zeros {
+                    format!("{number:0width$}")
+                } else {
+                    format!("{number:width$}")
+                }
+            }
+            Self::TypeColor => color_names(&text, name_colors, depth),
+        };
+        Ok(Value::String(filtered))
+    }
+}
+
+/// Registers the filters used by the template's values, e.g. `{pokemon | title | pad:12}`.
+///
+/// TinyTemplate supports a single formatter without arguments, but passes everything after the
+/// `|` as the formatter's name, so every distinct chain of filters is registered on its own.
+fn add_filters<'a>(
+    tt: &mut TinyTemplate<'a>,
+    template: &'a Template,
+    name_colors: &[(String, Option<Rgb<u8>>)],
+    depth: Option<ColorDepth>,
+) -> anyhow::Result<()> {
+    for (line, chain) in filter_chains(&template.text) {
+        let filters = chain
+            .split('|')
+            .map(|filter| {
+                Filter::parse(filter).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Unknown template filter `{}` at {}!",
+                        filter.trim(),
+                        template.location(line)
+                    )
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let name_colors = name_colors.to_vec();
+        tt.add_formatter(chain, move |value, output| {
+            let mut value = value.clone();
+            for filter in &filters {
+                value = filter.apply(&value, &name_colors, depth)?;
+            }
+            tinytemplate::format_unescaped(&value, output)
+        });
+    }
+    Ok(())
+}
+
+/// Finds the filters of every value tag along with the line of their first use, e.g.
+/// `title | pad:12` for `{pokemon | title | pad:12}`
+fn filter_chains(template: &str) -> Vec<(usize, &str)> {
+    let mut chains = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let tag = &rest[start..];
+        let line = template[..template.len() - tag.len()].matches('\n').count() + 1;
+        // Escaped braces, blocks like `{{ if shiny }}` and comments like `{# a | b #}` can't have
+        // filters
+        if rest[..start].ends_with('\\') {
+            rest = &tag[1..];
+            continue;
+        }
+        if tag.starts_with("{{") {
+            rest = tag.find("}}").map_or("", |end| &tag[end + 2..]);
+            continue;
+        }
+        if tag.starts_with("{#") {
+            rest = tag.find("#}").map_or("", |end| &tag[end + 2..]);
+            continue;
+        }
+        let Some(end) = tag.find('}') else {
+            break;
+        };
+
+        // The `-` markers trim whitespace around the tag
+        let value = tag[1..end].trim();
+        let value = value.strip_prefix('-').unwrap_or(value);
+        let value = value.strip_suffix('-').unwrap_or(value).trim();
+        if let Some((_, chain)) = value.split_once('|') {
+            let chain = chain.trim();
+            if !chains.iter().any(|&(_, known)| known == chain) {
+                chains.push((line, chain));
+            }
+        }
+        rest = &tag[end + 1..];
+    }
+    chains
+}
+
+/// Colors every Pokemon name in the text by its primary type, ignoring case and trying longer
+/// names first so that `MEWTWO` isn't colored as `MEW`
+fn color_names(
+    text: &str,
+    name_colors: &[(String, Option<Rgb<u8>>)],
+    depth: Option<ColorDepth>,
+) -> String {
+    let Some(depth) = depth else {
+        return String::from(text);
+    };
+    let mut name_colors = name_colors
+        .iter()
+        .filter_map(|(name, color)| Some((name.to_lowercase(), (*color)?)))
+        .filter(|(name, _)| !name.is_empty())
+        .collect::<Vec<_>>();
+    name_colors.sort_by_key(|(name, _)| Reverse(name.len()));
+
+    let mut colored = String::with_capacity(text.len());
+    let mut rest = text;
+    'outer: while let Some(c) = rest.chars().next() {
+        for (name, color) in &name_colors {
+            if let Some(matched) = rest.get(..name.len())
+                && matched.to_lowercase() == *name
+            {
+                colored.push_str(&depth.foreground(*color));
+                colored.push_str(matched);
+                colored.push_str(RESET_FOREGROUND);
+                rest = &rest[name.len()..];
+                continue 'outer;
+            }
+        }
+        colored.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    colored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRE: Rgb<u8> = Rgb([240, 128, 48]);
+
+    #[test]
+    fn filter_parse_reads_names_and_arguments() {
+        assert_eq!(Filter::parse("upper"), Some(Filter::Upper));
+        assert_eq!(Filter::parse(" title "), Some(Filter::Title));
+        assert_eq!(Filter::parse("pad: 12"), Some(Filter::Pad(12)));
+        assert_eq!(
+            Filter::parse("number"),
+            Some(Filter::Number {
+                width: 0,
+                zeros: false
+            })
+        );
+        assert_eq!(
+            Filter::parse("number:03"),
+            Some(Filter::Number {
+                width: 3,
+                zeros: true
+            })
+        );
+        assert_eq!(Filter::parse("type_color"), Some(Filter::TypeColor));
+    }
+
+    #[test]
+    fn filter_parse_rejects_unknown_filters_and_arguments() {
+        assert_eq!(Filter::parse("shout"), None);
+        assert_eq!(Filter::parse("upper:1"), None);
+        assert_eq!(Filter::parse("pad"), None);
+        assert_eq!(Filter::parse("pad:wide"), None);
+    }
+
+    #[test]
+    fn filter_apply_formats_the_value() {
+        let apply = |filter: &str, value: Value| {
+            Filter::parse(filter)
+                .unwrap()
+                .apply(&value, &[], None)
+                .unwrap()
+        };
+        assert_eq!(apply("upper", Value::from("Mr. Mime")), "MR. MIME");
+        assert_eq!(apply("title", Value::from("mr. MIME")), "Mr. Mime");
+        assert_eq!(apply("pad:6", Value::from("Mew")), "Mew   ");
+        assert_eq!(apply("number:03", Value::from(25)), "025");
+        assert_eq!(apply("number:3", Value::from(25)), " 25");
+        assert!(
+            Filter::Number {
+                width: 0,
+                zeros: false
+            }
+            .apply(&Value::from("Mew"), &[], None)
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn filter_chains_finds_each_chain_once_with_its_line() {
+        let template = "{pokemon | upper}\n{pokemon|upper} Lv.{level | number:3}\n{{ if shiny }}{pokemon}{{ endif }}";
+        assert_eq!(filter_chains(template), [(1, "upper"), (2, "number:3")]);
+    }
+
+    #[test]
+    fn filter_chains_skips_escaped_braces_and_blocks() {
+        assert!(filter_chains("\\{pokemon | upper} {{ for p in team }}{{ endfor }}").is_empty());
+        assert_eq!(filter_chains("{- pokemon | lower -}"), [(1, "lower")]);
+    }
+
+    #[test]
+    fn filter_chains_skips_comments() {
+        assert_eq!(
+            filter_chains("{# {pokemon | shout} or {pokemon} #}{pokemon | upper}"),
+            [(1, "upper")]
+        );
+    }
+
+    #[test]
+    fn color_names_prefers_longer_names() {
+        let name_colors = [
+            (String::from("Mew"), Some(FIRE)),
+            (String::from("Mewtwo"), Some(FIRE)),
+        ];
+        let depth = Some(ColorDepth::TrueColor);
+        let color = ColorDepth::TrueColor.foreground(FIRE);
+        assert_eq!(
+            color_names("MEWTWO and mew", &name_colors, depth),
+            format!("{color}MEWTWO{RESET_FOREGROUND} and {color}mew{RESET_FOREGROUND}")
+        );
+    }
+
+    #[test]
+    fn color_names_leaves_the_text_alone_without_colors() {
+        let name_colors = [
+            (String::from("Mew"), Some(FIRE)),
+            (String::from("Eevee"), None),
+        ];
+        assert_eq!(color_names("Mew", &name_colors, None), "Mew");
+        assert_eq!(
+            color_names("Eevee", &name_colors, Some(ColorDepth::Ansi256)),
+            "Eevee"
+        );
+    }
+}
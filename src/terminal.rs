@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use image::Rgb;
+
+// How long to wait for the terminal to answer a query before giving up
+const QUERY_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Size of the terminal in cells, preferring explicitly provided dimensions over detected ones
+pub fn size(columns: Option<u16>, rows: Option<u16>) -> Option<(u16, u16)> {
+    let detected = crossterm::terminal::size().ok();
+    Some((
+        columns.or(detected.map(|(columns, _)| columns))?,
+        rows.or(detected.map(|(_, rows)| rows))?,
+    ))
+}
+
+/// Size of a terminal cell in pixels
+pub fn cell_size() -> Option<(u32, u32)> {
+    if let Ok(size) = crossterm::terminal::window_size()
+        && size.width > 0
+        && size.height > 0
+        && size.columns > 0
+        && size.rows > 0
+    {
+        return Some((
+            u32::from(size.width / size.columns),
+            u32::from(size.height / size.rows),
+        ));
+    }
+
+    // Not every terminal fills in the pixel fields of `TIOCGWINSZ`, so fall back to asking for
+    // the cell size directly, which is answered with `CSI 6 ; height ; width t`
+    let response = query("\x1b[16t", |response| response.ends_with(b"t"))?;
+    let response = String::from_utf8(response).ok()?;
+    let mut fields = response
+        .trim_start_matches("\x1b[")
+        .trim_end_matches('t')
+        .split(';');
+    if fields.next()? != "6" {
+        return None;
+    }
+    let height = fields.next()?.parse().ok()?;
+    let width = fields.next()?.parse().ok()?;
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// Background color of the terminal, answered with `OSC 11 ; rgb:RRRR/GGGG/BBBB ST`
+pub fn background_color() -> Option<Rgb<u8>> {
+    let response = query("\x1b]11;?\x1b\\", |response| {
+        response.ends_with(b"\x07") || response.ends_with(b"\x1b\\")
+    })?;
+    let response = String::from_utf8(response).ok()?;
+    let (_, color) = response.split_once("rgb:")?;
+    let mut channels = color
+        .trim_end_matches(['\x07', '\x1b', '\\'])
+        .split('/')
+        .map(|channel| {
+            // Channels have 1 to 4 hex digits, scale them to 8 bits
+            let value = u32::from_str_radix(channel, 16).ok()?;
+            let max = 16u32.checked_pow(channel.len() as u32)? - 1;
+            Some((value * u32::from(u8::MAX) / max) as u8)
+        });
+    Some(Rgb([
+        channels.next()??,
+        channels.next()??,
+        channels.next()??,
+    ]))
+}
+
+/// Writes a query to the terminal and reads its response until `is_complete` returns `true`
+#[cfg(unix)]
+pub fn query(request: &str, is_complete: impl Fn(&[u8]) -> bool) -> Option<Vec<u8>> {
+    use std::{
+        fs::OpenOptions,
+        io::{IsTerminal, Read, Write},
+        os::fd::AsRawFd,
+        time::Instant,
+    };
+
+    // The output isn't displayed by the terminal when it's redirected, so waiting for an answer
+    // would only slow down scripts
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    crossterm::terminal::enable_raw_mode().ok()?;
+
+    let mut response = Vec::new();
+    let written = tty
+        .write_all(request.as_bytes())
+        .and_then(|_| tty.flush())
+        .is_ok();
+    let deadline = Instant::now() + QUERY_TIMEOUT;
+    while written && !is_complete(&response) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let mut poll_fd = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: `poll_fd` is a valid `pollfd` for the duration of the call
+        let ready = unsafe { libc::poll(&mut poll_fd, 1, remaining.as_millis() as libc::c_int) };
+        if ready <= 0 {
+            break;
+        }
+        let mut buf = [0; 64];
+        match tty.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => response.extend_from_slice(&buf[..n]),
+        }
+    }
+
+    crossterm::terminal::disable_raw_mode().ok()?;
+
+    is_complete(&response).then_some(response)
+}
+
+#[cfg(not(unix))]
+pub fn query(_request: &str, _is_complete: impl Fn(&[u8]) -> bool) -> Option<Vec<u8>> {
+    None
+}
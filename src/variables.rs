@@ -0,0 +1,108 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+// Environment variables starting with this prefix are passed to the template, e.g.
+// `POKEMONSAY_VAR_GREETING` as `{greeting}`
+const ENV_PREFIX: &str = "POKEMONSAY_VAR_";
+
+/// Parses a `--var` argument like `greeting=Hello`
+pub fn parse_var(var: &str) -> Result<(String, String), String> {
+    let (name, value) = var
+        .split_once('=')
+        .ok_or_else(|| format!("Expected `NAME=VALUE` but found `{var}`"))?;
+    validate_name(name)?;
+    Ok((String::from(name), String::from(value)))
+}
+
+/// Collects the variables available to the template. Variables from the environment override the
+/// built-in ones, and `--var` arguments override both.
+pub fn collect(vars: &[(String, String)]) -> anyhow::Result<BTreeMap<String, String>> {
+    let now = chrono::Local::now();
+    let mut variables = BTreeMap::from([
+        (String::from("hostname"), hostname().unwrap_or_default()),
+        (String::from("user"), user().unwrap_or_default()),
+        (String::from("date"), now.format("%Y-%m-%d").to_string()),
+        (String::from("time"), now.format("%H:%M").to_string()),
+        (String::from("branch"), git_branch().unwrap_or_default()),
+    ]);
+
+    for (key, value) in env::vars_os() {
+        let Some(name) = key.to_str().and_then(|key| key.strip_prefix(ENV_PREFIX)) else {
+            continue;
+        };
+        let name = name.to_lowercase();
+        validate_name(&name).map_err(|err| anyhow::anyhow!("{err} in `{ENV_PREFIX}*`!"))?;
+        let value = value.into_string().map_err(|_| {
+            anyhow::anyhow!("`{ENV_PREFIX}{}` isn't valid UTF-8!", name.to_uppercase())
+        })?;
+        variables.insert(name, value);
+    }
+
+    variables.extend(vars.iter().cloned());
+    Ok(variables)
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid variable name `{name}`, only letters, digits and `_` are allowed"
+        ))
+    }
+}
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    // SAFETY: `buffer` is valid for writes of its length for the duration of the call
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return None;
+    }
+    let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    Some(String::from_utf8_lossy(&buffer[..end]).into_owned())
+}
+
+#[cfg(not(unix))]
+fn hostname() -> Option<String> {
+    env::var("COMPUTERNAME").ok()
+}
+
+fn user() -> Option<String> {
+    env::var("USER").or_else(|_| env::var("USERNAME")).ok()
+}
+
+/// Reads the current git branch from `.git/HEAD` of the enclosing repository, or the abbreviated
+/// commit hash when the `HEAD` is detached
+fn git_branch() -> Option<String> {
+    let git_dir = find_git_dir(&env::current_dir().ok()?)?;
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: ") {
+        Some(reference) => Some(String::from(
+            reference.strip_prefix("refs/heads/").unwrap_or(reference),
+        )),
+        None => head.get(..7).map(String::from),
+    }
+}
+
+/// Finds the `.git` directory of the repository containing `dir`, following the `.git` files of
+/// worktrees and submodules
+fn find_git_dir(dir: &Path) -> Option<PathBuf> {
+    for dir in dir.ancestors() {
+        let git = dir.join(".git");
+        if git.is_dir() {
+            return Some(git);
+        }
+        if git.is_file() {
+            let contents = fs::read_to_string(&git).ok()?;
+            let git_dir = contents.trim().strip_prefix("gitdir: ")?;
+            return Some(dir.join(git_dir));
+        }
+    }
+    None
+}